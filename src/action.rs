@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, PoisonError};
+
+use crate::global::{GlobalHotkey, GlobalHotkeyManager, GlobalHotkeyManagerImpl, HotKeyParseError};
+
+/// Map of hotkeys to actions of an application defined type, usually an enum of commands. Instead
+/// of running a callback for each hotkey, the action of the triggered hotkey is returned by
+/// `recv`. This is built on top of a `GlobalHotkeyManager`.
+///
+/// Multiple hotkeys can be bound to the same action. Changing the bindings while the map is
+/// listening stops and restarts the underlying `GlobalHotkeyManager`, so all hotkeys are briefly
+/// unregistered.
+///
+#[derive(Debug)]
+pub struct ActionMap<A: Clone + PartialEq + Send + 'static> {
+    manager: GlobalHotkeyManager<()>,
+    bindings: Mutex<Vec<(String, A)>>,
+    next_name: AtomicUsize,
+    sender: Sender<A>,
+    receiver: Mutex<Receiver<A>>,
+}
+
+impl<A: Clone + PartialEq + Send + 'static> ActionMap<A> {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            manager: GlobalHotkeyManager::new(),
+            bindings: Mutex::new(Vec::new()),
+            next_name: AtomicUsize::new(0),
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+
+    /// Parse the hotkey and bind it to the action, for example `"Ctrl + Shift + S"`.
+    pub fn bind(&self, hotkey: &str, action: A) -> Result<(), HotKeyParseError> {
+        let mut hotkey: GlobalHotkey<()> = hotkey.try_into()?;
+        let sender = self.sender.clone();
+        let triggered = action.clone();
+        hotkey.set_action(move || {
+            let _ = sender.send(triggered.clone());
+        });
+
+        let name = format!("action-{}", self.next_name.fetch_add(1, Ordering::SeqCst));
+        self.restart_with(|| {
+            self.manager.add_hotkey(name.clone(), hotkey);
+            self.lock_bindings().push((name, action));
+        });
+
+        Ok(())
+    }
+
+    /// Remove all hotkeys bound to the action and bind the new hotkey instead. The old bindings
+    /// are kept if the hotkey can't be parsed.
+    pub fn rebind(&self, action: A, hotkey: &str) -> Result<(), HotKeyParseError> {
+        // Parse up front, so that a parse error doesn't remove the old bindings
+        let _: GlobalHotkey<()> = hotkey.try_into()?;
+        self.unbind(&action);
+        self.bind(hotkey, action)
+    }
+
+    /// Remove all hotkeys bound to the action. Returns the number of removed hotkeys.
+    pub fn unbind(&self, action: &A) -> usize {
+        self.restart_with(|| {
+            let mut bindings = self.lock_bindings();
+            let before = bindings.len();
+            bindings.retain(|(name, bound)| {
+                if bound == action {
+                    self.manager.remove_hotkey(name.clone());
+                    false
+                } else {
+                    true
+                }
+            });
+            before - bindings.len()
+        })
+    }
+
+    /// Get all actions with at least one bound hotkey.
+    pub fn actions(&self) -> Vec<A> {
+        let mut actions: Vec<A> = Vec::new();
+        for (_, action) in self.lock_bindings().iter() {
+            if !actions.contains(action) {
+                actions.push(action.clone());
+            }
+        }
+        actions
+    }
+
+    /// Start listening for the bound hotkeys. See `GlobalHotkeyManagerImpl::start`.
+    pub fn start(&self) {
+        self.manager.start();
+    }
+
+    /// Stop listening for the bound hotkeys. See `GlobalHotkeyManagerImpl::stop`.
+    pub fn stop(&self) -> bool {
+        self.manager.stop()
+    }
+
+    /// Check if the bound hotkeys are being listened for. See
+    /// `GlobalHotkeyManagerImpl::is_listening`.
+    pub fn is_listening(&self) -> bool {
+        self.manager.is_listening()
+    }
+
+    /// Block until a bound hotkey is triggered and return its action. Returns `None` if another
+    /// thread panicked while receiving.
+    pub fn recv(&self) -> Option<A> {
+        self.receiver.lock().ok()?.recv().ok()
+    }
+
+    /// Return the action of a triggered hotkey if there is one, without blocking.
+    pub fn try_recv(&self) -> Option<A> {
+        self.receiver.lock().ok()?.try_recv().ok()
+    }
+
+    fn lock_bindings(&self) -> std::sync::MutexGuard<'_, Vec<(String, A)>> {
+        self.bindings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `f` while the manager is stopped, and start it again afterwards if it was listening.
+    fn restart_with<R>(&self, f: impl FnOnce() -> R) -> R {
+        let was_listening = self.manager.stop();
+        let result = f();
+        if was_listening {
+            self.manager.start();
+        }
+        result
+    }
+}
+
+impl<A: Clone + PartialEq + Send + 'static> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,16 +1,30 @@
 use crate::keys::VirtualKey;
+use crate::HotkeyId;
 use std::error::Error;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result;
 
+#[derive(Clone, PartialEq, Eq)]
 pub enum HotkeyError {
     InvalidKey(String),
     InvalidKeyChar(char),
     NotAModkey(VirtualKey),
     RegistrationFailed,
     UnregistrationFailed,
+    UnknownHotkeyId(HotkeyId),
+    BackendGone,
+    MissingModifiers(VirtualKey),
+    WindowCreationFailed(u32),
+    InvalidKeyCode(u16),
+    SendInputFailed,
+    IdsExhausted,
+    MessageLoopFailed(u32),
+    ModifierAsMainKey(VirtualKey),
+    MissingContext,
+    UnsupportedPlatform,
+    InvalidTapCount(u8),
 }
 
 impl Display for HotkeyError {
@@ -24,6 +38,40 @@ impl Display for HotkeyError {
                 "Hotkey registration failed. Hotkey or Id might be in use already"
             ),
             HotkeyError::UnregistrationFailed => write!(f, "Hotkey unregistration failed"),
+            HotkeyError::UnknownHotkeyId(ref id) => write!(f, "No hotkey registered for id {}", id),
+            HotkeyError::BackendGone => write!(f, "Hotkey manager backend thread is not running"),
+            HotkeyError::MissingModifiers(ref vkey) => write!(
+                f,
+                "Hotkey {} without modifiers would block typing the key in all applications",
+                vkey
+            ),
+            HotkeyError::WindowCreationFailed(ref code) => write!(
+                f,
+                "Failed to create the hidden window for receiving hotkeys (os error {})",
+                code
+            ),
+            HotkeyError::InvalidKeyCode(ref code) => {
+                write!(f, "invalid virtual keycode 0x{:x}", code)
+            }
+            HotkeyError::SendInputFailed => write!(f, "Failed to send the keyboard input"),
+            HotkeyError::IdsExhausted => write!(f, "No free hotkey id left"),
+            HotkeyError::MessageLoopFailed(ref code) => {
+                write!(f, "Failed to receive hotkey messages (os error {})", code)
+            }
+            HotkeyError::ModifierAsMainKey(ref vkey) => write!(
+                f,
+                "Modifier {} can't be the main key of a hotkey, pass it as a modifier instead",
+                vkey
+            ),
+            HotkeyError::MissingContext => {
+                write!(f, "No hotkey manager context of the requested type is set")
+            }
+            HotkeyError::UnsupportedPlatform => {
+                write!(f, "Hotkeys are only supported on windows")
+            }
+            HotkeyError::InvalidTapCount(ref taps) => {
+                write!(f, "A multi-tap hotkey needs at least 2 taps, got {}", taps)
+            }
         }
     }
 }
@@ -39,6 +87,40 @@ impl Debug for HotkeyError {
                 "Hotkey registration failed. Hotkey or Id might be in use already"
             ),
             HotkeyError::UnregistrationFailed => write!(f, "Hotkey unregistration failed"),
+            HotkeyError::UnknownHotkeyId(ref id) => write!(f, "No hotkey registered for id {}", id),
+            HotkeyError::BackendGone => write!(f, "Hotkey manager backend thread is not running"),
+            HotkeyError::MissingModifiers(ref vkey) => write!(
+                f,
+                "Hotkey {} without modifiers would block typing the key in all applications",
+                vkey
+            ),
+            HotkeyError::WindowCreationFailed(ref code) => write!(
+                f,
+                "Failed to create the hidden window for receiving hotkeys (os error {})",
+                code
+            ),
+            HotkeyError::InvalidKeyCode(ref code) => {
+                write!(f, "invalid virtual keycode 0x{:x}", code)
+            }
+            HotkeyError::SendInputFailed => write!(f, "Failed to send the keyboard input"),
+            HotkeyError::IdsExhausted => write!(f, "No free hotkey id left"),
+            HotkeyError::MessageLoopFailed(ref code) => {
+                write!(f, "Failed to receive hotkey messages (os error {})", code)
+            }
+            HotkeyError::ModifierAsMainKey(ref vkey) => write!(
+                f,
+                "Modifier {} can't be the main key of a hotkey, pass it as a modifier instead",
+                vkey
+            ),
+            HotkeyError::MissingContext => {
+                write!(f, "No hotkey manager context of the requested type is set")
+            }
+            HotkeyError::UnsupportedPlatform => {
+                write!(f, "Hotkeys are only supported on windows")
+            }
+            HotkeyError::InvalidTapCount(ref taps) => {
+                write!(f, "A multi-tap hotkey needs at least 2 taps, got {}", taps)
+            }
         }
     }
 }
@@ -48,3 +130,58 @@ impl Error for HotkeyError {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_variant_can_be_cloned() {
+        let errors = [
+            HotkeyError::InvalidKey("NOKEY".to_string()),
+            HotkeyError::InvalidKeyChar('€'),
+            HotkeyError::NotAModkey(VirtualKey::A),
+            HotkeyError::RegistrationFailed,
+            HotkeyError::UnregistrationFailed,
+            HotkeyError::UnknownHotkeyId(HotkeyId::new(3)),
+            HotkeyError::BackendGone,
+            HotkeyError::MissingModifiers(VirtualKey::A),
+            HotkeyError::WindowCreationFailed(5),
+            HotkeyError::InvalidKeyCode(0xFF),
+            HotkeyError::SendInputFailed,
+            HotkeyError::IdsExhausted,
+            HotkeyError::MessageLoopFailed(6),
+            HotkeyError::ModifierAsMainKey(VirtualKey::Shift),
+            HotkeyError::MissingContext,
+            HotkeyError::UnsupportedPlatform,
+            HotkeyError::InvalidTapCount(1),
+        ];
+
+        for error in &errors {
+            // Fails to compile when a variant is added, as a reminder to add it above
+            match error {
+                HotkeyError::InvalidKey(_)
+                | HotkeyError::InvalidKeyChar(_)
+                | HotkeyError::NotAModkey(_)
+                | HotkeyError::RegistrationFailed
+                | HotkeyError::UnregistrationFailed
+                | HotkeyError::UnknownHotkeyId(_)
+                | HotkeyError::BackendGone
+                | HotkeyError::MissingModifiers(_)
+                | HotkeyError::WindowCreationFailed(_)
+                | HotkeyError::InvalidKeyCode(_)
+                | HotkeyError::SendInputFailed
+                | HotkeyError::IdsExhausted
+                | HotkeyError::MessageLoopFailed(_)
+                | HotkeyError::ModifierAsMainKey(_)
+                | HotkeyError::MissingContext
+                | HotkeyError::UnsupportedPlatform
+                | HotkeyError::InvalidTapCount(_) => {}
+            }
+
+            let clone = error.clone();
+            assert_eq!(&clone, error);
+            assert_eq!(clone.to_string(), error.to_string());
+        }
+    }
+}
@@ -1,18 +1,40 @@
 use rustc_hash::FxHashMap;
 
-use crate::{HotkeyId, HotkeyManager, HotkeyManagerImpl, ModifiersKey, VirtualKey};
+use crate::error::HotkeyError;
+use crate::{
+    HotkeyId, HotkeyManager, HotkeyManagerImpl, InterruptHandle, ModifiersKey, VirtualKey,
+};
 use core::fmt;
+use std::ops::Range;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc, Mutex,
+    Arc, Mutex, TryLockError,
 };
+use std::thread::JoinHandle;
+use std::thread::ThreadId;
 
 #[derive(Clone)]
 pub struct GlobalHotkey<T> {
     key: VirtualKey,
     modifiers: Option<Vec<ModifiersKey>>,
     extras: Option<Vec<VirtualKey>>,
-    action: Option<Arc<Mutex<dyn Fn() -> T + Send + 'static>>>, // Callback needs to be Send too
+    action: Option<GlobalHotkeyAction<T>>,
+}
+
+/// Action of a `GlobalHotkey`. Shared actions are registered as is, so they are neither boxed again
+/// nor called through a mutex.
+enum GlobalHotkeyAction<T> {
+    Boxed(Arc<Mutex<dyn Fn() -> T + Send + 'static>>), // Callback needs to be Send too
+    Shared(Arc<dyn Fn() -> T + Send + Sync + 'static>),
+}
+
+impl<T> Clone for GlobalHotkeyAction<T> {
+    fn clone(&self) -> Self {
+        match self {
+            GlobalHotkeyAction::Boxed(action) => GlobalHotkeyAction::Boxed(action.clone()),
+            GlobalHotkeyAction::Shared(action) => GlobalHotkeyAction::Shared(action.clone()),
+        }
+    }
 }
 
 impl<T> fmt::Debug for GlobalHotkey<T>
@@ -35,29 +57,233 @@ where
     }
 }
 
+/// Callback that is executed when a hotkey can't be registered, see
+/// `GlobalHotkeyManagerImpl::set_on_register_error`.
+type RegisterErrorFn<T> = dyn Fn(&str, &GlobalHotkey<T>, &HotkeyError) + Send + 'static;
+
+/// Shared slot for the registration error callback of a `GlobalHotkeyManager`.
+struct RegisterErrorHook<T>(Arc<Mutex<Option<Box<RegisterErrorFn<T>>>>>);
+
+impl<T> Clone for RegisterErrorHook<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> fmt::Debug for RegisterErrorHook<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The hook stays locked while it runs, so it may format the manager itself
+        let is_some = match self.0.try_lock() {
+            Ok(hook) => hook.is_some(),
+            Err(TryLockError::Poisoned(err)) => err.into_inner().is_some(),
+            Err(TryLockError::WouldBlock) => return f.write_str("<locked>"),
+        };
+        f.write_str(if is_some {
+            "Some(Fn(&str, &GlobalHotkey<T>, &HotkeyError))"
+        } else {
+            "None"
+        })
+    }
+}
+
+impl<T> RegisterErrorHook<T> {
+    /// Log the failed registration and pass it to the callback, if there is one.
+    ///
+    fn report(&self, name: &str, hotkey: &GlobalHotkey<T>, err: &HotkeyError) {
+        log_error!("failed to register keybinding {:?}: {}", hotkey.key, err);
+        if let Some(hook) = self.0.lock().unwrap().as_ref() {
+            hook(name, hotkey, err);
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GlobalHotkeyManager<T: Send + 'static> {
     hotkeys: Arc<Mutex<FxHashMap<String, GlobalHotkey<T>>>>,
     manager: Arc<Mutex<HotkeyManager<T>>>,
     listening: Arc<AtomicBool>,
-    key_ids: Arc<Mutex<Vec<HotkeyId>>>,
+    no_repeat: Arc<AtomicBool>,
+    key_ids: Arc<Mutex<FxHashMap<String, HotkeyId>>>,
+    // Obtained up front, since the manager can't hand out a handle while its event loop is running
+    interrupt: Arc<InterruptHandle>,
+    // Thread that executes the actions, `stop` can't wait for the listener from there
+    backend_thread: Option<ThreadId>,
+    listener: Arc<Mutex<Option<JoinHandle<()>>>>,
+    // Held while accessing the manager from outside the listener thread, to keep the listener from
+    // locking the manager again after its event loop was interrupted
+    gate: Arc<Mutex<()>>,
+    on_register_error: RegisterErrorHook<T>,
+}
+
+/// Two `GlobalHotkey`s are equal when they describe the same key combination. The modifiers and
+/// extra keys are normalized on construction, so the order in which they were specified doesn't
+/// matter, and an empty list is the same as `None`. The action is not compared.
+impl<T> PartialEq for GlobalHotkey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.modifiers == other.modifiers && self.extras == other.extras
+    }
 }
 
+impl<T> Eq for GlobalHotkey<T> {}
+
 impl<T: Send + 'static> GlobalHotkey<T> {
     pub fn set_action(&mut self, action: impl Fn() -> T + Send + 'static) {
-        self.action = Some(Arc::new(Mutex::new(action)));
+        self.action = Some(GlobalHotkeyAction::Boxed(Arc::new(Mutex::new(action))));
+    }
+
+    /// Get the modifiers of the hotkey as a list, in the conventional order Ctrl, Alt, Shift, Win.
+    /// `ModifiersKey::NoRepeat` and `ModifiersKey::Non` are not key presses, so they are left out.
+    pub fn modifier_list(&self) -> Vec<ModifiersKey> {
+        let mut modifiers: Vec<ModifiersKey> = self
+            .modifiers
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|modifier| !matches!(modifier, ModifiersKey::NoRepeat | ModifiersKey::Non))
+            .collect();
+        modifiers.sort_by_key(|modifier| match modifier {
+            ModifiersKey::Ctrl | ModifiersKey::LCtrl | ModifiersKey::RCtrl => 0,
+            ModifiersKey::Alt | ModifiersKey::LAlt | ModifiersKey::RAlt => 1,
+            ModifiersKey::Shift | ModifiersKey::LShift | ModifiersKey::RShift => 2,
+            _ => 3,
+        });
+        modifiers
+    }
+
+    /// Get a human-readable representation of the hotkey for menus and settings, like
+    /// `"Ctrl+Shift+S"`. The modifiers are ordered as in `modifier_list`, followed by the main key
+    /// and the extra keys. Unlike the string format accepted by the parser, this is not meant to be
+    /// parsed again.
+    pub fn display_human(&self) -> String {
+        let modifiers = self
+            .modifier_list()
+            .into_iter()
+            .map(|modifier| match modifier {
+                ModifiersKey::Ctrl => "Ctrl",
+                ModifiersKey::LCtrl => "Left Ctrl",
+                ModifiersKey::RCtrl => "Right Ctrl",
+                ModifiersKey::Alt => "Alt",
+                ModifiersKey::LAlt => "Left Alt",
+                ModifiersKey::RAlt => "Right Alt",
+                ModifiersKey::Shift => "Shift",
+                ModifiersKey::LShift => "Left Shift",
+                ModifiersKey::RShift => "Right Shift",
+                ModifiersKey::Win => "Win",
+                ModifiersKey::LWin => "Left Win",
+                ModifiersKey::RWin => "Right Win",
+                ModifiersKey::NoRepeat | ModifiersKey::Non => unreachable!(),
+            });
+        let keys = std::iter::once(&self.key)
+            .chain(self.extras.as_deref().unwrap_or_default())
+            .map(VirtualKey::short_name);
+
+        modifiers.chain(keys).collect::<Vec<_>>().join("+")
+    }
+
+    /// Check if the hotkey consists of the given key combination. The keys are compared by their
+    /// keycode, and the order of the modifiers and extra keys doesn't matter.
+    pub fn matches(
+        &self,
+        key: VirtualKey,
+        modifiers: &[ModifiersKey],
+        extras: &[VirtualKey],
+    ) -> bool {
+        self.key == key
+            && normalize_modifiers(Some(modifiers.to_vec())) == self.modifiers
+            && normalize_extras(Some(extras.to_vec())) == self.extras
+    }
+
+    /// Register the hotkey and its action with the given `HotkeyManager`.
+    ///
+    fn register(&self, manager: &mut HotkeyManager<T>) -> Result<HotkeyId, HotkeyError> {
+        match self.action.clone() {
+            // Register with an action if present
+            Some(GlobalHotkeyAction::Boxed(action)) => manager.register_extrakeys(
+                self.key,
+                self.modifiers.as_deref(),
+                self.extras.as_deref(),
+                Some(move || {
+                    let action = action.lock().unwrap();
+                    action()
+                }),
+            ),
+            Some(GlobalHotkeyAction::Shared(action)) => manager.register_shared(
+                self.key,
+                self.modifiers.as_deref(),
+                self.extras.as_deref(),
+                action,
+            ),
+            // Register without an action if None
+            None => manager.register_extrakeys(
+                self.key,
+                self.modifiers.as_deref(),
+                self.extras.as_deref(),
+                None::<fn() -> T>,
+            ),
+        }
+    }
+}
+
+impl<T: Send + 'static> GlobalHotkeyManager<T> {
+    /// Spawn the thread that runs the event loop for as long as the manager is listening.
+    ///
+    /// Once the manager stops listening, the thread unregisters the hotkeys before exiting, so this
+    /// also happens when `stop` can't wait for the thread.
+    ///
+    fn spawn_listener(&self) -> JoinHandle<()> {
+        let hkm = self.manager.clone();
+        let listening = self.listening.clone();
+        let gate = self.gate.clone();
+        let key_ids = self.key_ids.clone();
+
+        std::thread::spawn(move || {
+            // Lock the Mutex inside the thread, instead of moving the MutexGuard
+            while listening.load(Ordering::SeqCst) {
+                // Wait for pending accesses to the manager before locking it again
+                drop(gate.lock().unwrap());
+                hkm.lock().unwrap().event_loop();
+            }
+
+            let mut hotkey_manager = hkm.lock().unwrap();
+            let mut key_ids = key_ids.lock().unwrap();
+            for (_, hotkey_id) in key_ids.drain() {
+                if let Err(e) = hotkey_manager.unregister(hotkey_id) {
+                    log_error!("failed to unregister keybinding {:?}: {}", hotkey_id, e);
+                }
+            }
+        })
+    }
+
+    /// Run `f` with exclusive access to the inner `HotkeyManager`. If the manager is listening,
+    /// the event loop is interrupted to release the manager and resumes once `f` returns.
+    ///
+    fn with_manager<R>(&self, f: impl FnOnce(&mut HotkeyManager<T>) -> R) -> R {
+        let _gate = self.gate.lock().unwrap();
+        if self.listening.load(Ordering::SeqCst) {
+            self.interrupt.interrupt();
+        }
+
+        let mut hotkey_manager = self.manager.lock().unwrap();
+        f(&mut hotkey_manager)
     }
 }
 
 impl<T: Send + 'static> Default for GlobalHotkeyManager<T> {
     fn default() -> Self {
-        let mut hkm = HotkeyManager::new();
-        hkm.set_no_repeat(false);
+        let hkm = HotkeyManager::builder().no_repeat(false).build();
+        let interrupt = hkm.interrupt_handle();
+        let backend_thread = hkm.backend_thread();
         Self {
             manager: Arc::new(Mutex::new(hkm)),
             listening: Arc::new(AtomicBool::new(false)),
+            no_repeat: Arc::new(AtomicBool::new(false)),
             hotkeys: Arc::new(Mutex::new(FxHashMap::default())),
-            key_ids: Arc::new(Mutex::new(Vec::new())),
+            key_ids: Arc::new(Mutex::new(FxHashMap::default())),
+            gate: Arc::new(Mutex::new(())),
+            interrupt: Arc::new(interrupt),
+            backend_thread,
+            listener: Arc::new(Mutex::new(None)),
+            on_register_error: RegisterErrorHook(Arc::new(Mutex::new(None))),
         }
     }
 }
@@ -72,10 +298,79 @@ pub trait GlobalHotkeyManagerImpl<T> {
         extras: Option<Vec<VirtualKey>>,
         callback: Option<impl Fn() -> T + Send + 'static>,
     );
+    /// Same as `register_hotkey` but with a shared callback, so that one `Arc` can back the
+    /// callbacks of multiple hotkeys.
+    fn register_shared_hotkey(
+        &self,
+        name: String,
+        key: VirtualKey,
+        modifiers: Option<Vec<ModifiersKey>>,
+        extras: Option<Vec<VirtualKey>>,
+        callback: Arc<dyn Fn() -> T + Send + Sync + 'static>,
+    );
+    /// Same as `register_hotkey` but the callback receives the name the hotkey is registered
+    /// under, so that one callback can serve multiple hotkeys and still tell them apart.
+    fn register_named_hotkey(
+        &self,
+        name: String,
+        key: VirtualKey,
+        modifiers: Option<Vec<ModifiersKey>>,
+        extras: Option<Vec<VirtualKey>>,
+        callback: impl Fn(&str) -> T + Send + 'static,
+    );
+    /// Same as `register_hotkey` but replaces the hotkey registered under the same name right
+    /// away if the manager is listening, by unregistering the old hotkey and registering the new
+    /// one. When the manager isn't listening, the new hotkey will be registered by `start`.
+    fn replace_hotkey(
+        &self,
+        name: String,
+        key: VirtualKey,
+        modifiers: Option<Vec<ModifiersKey>>,
+        extras: Option<Vec<VirtualKey>>,
+        callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<(), HotkeyError>;
     fn add_hotkey(&self, name: String, hotkey: GlobalHotkey<T>);
     fn remove_hotkey(&self, name: String) -> Option<GlobalHotkey<T>>;
+    /// Get the names of all hotkeys added to the manager.
+    fn names(&self) -> Vec<String>;
+    /// Check if a hotkey with the given name has been added to the manager.
+    fn contains(&self, name: &str) -> bool;
+    /// Remove all hotkeys from the manager. This doesn't affect hotkeys that were already
+    /// registered by `start`.
+    fn clear(&self);
+    /// Enable or disable the automatically applied `ModifiersKey::NoRepeat` modifier. Unlike the
+    /// `HotkeyManager`s, where this option defaults to `true`, it defaults to `false` for the
+    /// `GlobalHotkeyManager`, so all hotkeys retrigger while being held down.
+    ///
+    /// Note: This only applies to hotkeys registered after calling this function, so the hotkeys
+    /// of a running manager are not affected until it is stopped and started again.
+    fn set_no_repeat(&self, no_repeat: bool);
+    /// Set a callback that is executed whenever a hotkey can't be registered, for example because
+    /// the key combination is already taken by another application. The callback receives the
+    /// name of the hotkey, the hotkey itself and the error, so the application can handle all
+    /// failures in one place, like showing a notification. The failure is logged in any case.
+    ///
+    /// The callback is executed by `start` and `replace_hotkey` while the manager is locked, so it
+    /// must not call back into the manager.
+    fn set_on_register_error(
+        &self,
+        callback: impl Fn(&str, &GlobalHotkey<T>, &HotkeyError) + Send + 'static,
+    );
     fn start(&self);
+    /// Stop listening for hotkeys. This interrupts the event loop, waits for the listener thread to
+    /// finish and unregisters all hotkeys before returning, so `start` can be called again right
+    /// away without running into already registered hotkeys.
+    ///
+    /// When called from a hotkey action, for example to stop listening once a hotkey was pressed,
+    /// the listener can't finish before the action returns. In that case `stop` returns right away,
+    /// and the hotkeys are unregistered once the action returned. A following `start` waits for
+    /// this to complete.
+    ///
+    /// Returns `false` if the manager wasn't listening.
     fn stop(&self) -> bool;
+    /// Check if the manager is listening for hotkeys, meaning `start` was called and the manager
+    /// hasn't been stopped since.
+    fn is_listening(&self) -> bool;
     #[cfg(feature = "upcoming_update")]
     fn update(&mut self);
 }
@@ -98,15 +393,93 @@ impl<T: Send + 'static> GlobalHotkeyManagerImpl<T> for GlobalHotkeyManager<T> {
             name,
             GlobalHotkey {
                 key,
-                modifiers,
-                extras,
-                action: callback.map(|cb| {
-                    Arc::new(Mutex::new(cb)) as Arc<Mutex<dyn Fn() -> T + Send + 'static>>
-                }),
+                modifiers: normalize_modifiers(modifiers),
+                extras: normalize_extras(extras),
+                action: callback
+                    .map(|cb| GlobalHotkeyAction::Boxed(Arc::new(Mutex::new(cb)) as Arc<Mutex<_>>)),
+            },
+        );
+    }
+
+    fn register_shared_hotkey(
+        &self,
+        name: String,
+        key: VirtualKey,
+        modifiers: Option<Vec<ModifiersKey>>,
+        extras: Option<Vec<VirtualKey>>,
+        callback: Arc<dyn Fn() -> T + Send + Sync + 'static>,
+    ) {
+        let mut hotkeys = self.hotkeys.lock().unwrap();
+        hotkeys.insert(
+            name,
+            GlobalHotkey {
+                key,
+                modifiers: normalize_modifiers(modifiers),
+                extras: normalize_extras(extras),
+                action: Some(GlobalHotkeyAction::Shared(callback)),
             },
         );
     }
 
+    fn register_named_hotkey(
+        &self,
+        name: String,
+        key: VirtualKey,
+        modifiers: Option<Vec<ModifiersKey>>,
+        extras: Option<Vec<VirtualKey>>,
+        callback: impl Fn(&str) -> T + Send + 'static,
+    ) {
+        let hotkey_name = name.clone();
+        self.register_hotkey(
+            name,
+            key,
+            modifiers,
+            extras,
+            Some(move || callback(&hotkey_name)),
+        );
+    }
+
+    fn replace_hotkey(
+        &self,
+        name: String,
+        key: VirtualKey,
+        modifiers: Option<Vec<ModifiersKey>>,
+        extras: Option<Vec<VirtualKey>>,
+        callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<(), HotkeyError> {
+        if !self.listening.load(Ordering::SeqCst) {
+            self.register_hotkey(name, key, modifiers, extras, callback);
+            return Ok(());
+        }
+
+        self.with_manager(|hotkey_manager| {
+            let mut hotkeys = self.hotkeys.lock().unwrap();
+            let mut key_ids = self.key_ids.lock().unwrap();
+
+            // Only forget the old id once it is unregistered, so `stop` can still unregister it
+            if let Some(&hotkey_id) = key_ids.get(&name) {
+                hotkey_manager.unregister(hotkey_id)?;
+                key_ids.remove(&name);
+            }
+
+            let hotkey = GlobalHotkey {
+                key,
+                modifiers: normalize_modifiers(modifiers),
+                extras: normalize_extras(extras),
+                action: callback
+                    .map(|cb| GlobalHotkeyAction::Boxed(Arc::new(Mutex::new(cb)) as Arc<Mutex<_>>)),
+            };
+            let result = hotkey.register(hotkey_manager);
+            if let Err(e) = &result {
+                self.on_register_error.report(&name, &hotkey, e);
+            }
+            hotkeys.insert(name.clone(), hotkey);
+            key_ids.insert(name, result?);
+
+            Ok(())
+        })
+    }
+
     fn add_hotkey(&self, name: String, hotkey: GlobalHotkey<T>) {
         let mut hotkeys = self.hotkeys.lock().unwrap();
         hotkeys.insert(name, hotkey);
@@ -117,9 +490,35 @@ impl<T: Send + 'static> GlobalHotkeyManagerImpl<T> for GlobalHotkeyManager<T> {
         hotkeys.remove(&key)
     }
 
+    fn names(&self) -> Vec<String> {
+        let hotkeys = self.hotkeys.lock().unwrap();
+        hotkeys.keys().cloned().collect()
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        let hotkeys = self.hotkeys.lock().unwrap();
+        hotkeys.contains_key(name)
+    }
+
+    fn clear(&self) {
+        let mut hotkeys = self.hotkeys.lock().unwrap();
+        hotkeys.clear();
+    }
+
+    fn set_no_repeat(&self, no_repeat: bool) {
+        self.no_repeat.store(no_repeat, Ordering::SeqCst);
+        self.with_manager(|hotkey_manager| hotkey_manager.set_no_repeat(no_repeat));
+    }
+
+    fn set_on_register_error(
+        &self,
+        callback: impl Fn(&str, &GlobalHotkey<T>, &HotkeyError) + Send + 'static,
+    ) {
+        *self.on_register_error.0.lock().unwrap() = Some(Box::new(callback));
+    }
+
     #[cfg(feature = "upcoming_update")]
     fn update(&mut self) {
-        let listening = self.listening.clone();
         let hotkey_manager = self.manager.clone();
 
         // Lock bindings to access keybindings
@@ -128,169 +527,197 @@ impl<T: Send + 'static> GlobalHotkeyManagerImpl<T> for GlobalHotkeyManager<T> {
         let mut key_ids = self.key_ids.lock().unwrap();
 
         if let Err(e) = hotkey_manager_mut.unregister_all() {
-            eprintln!("failed to unregister all keybindings: {}", e);
+            log_error!("failed to unregister all keybindings: {}", e);
         }
 
         let handle = hotkey_manager_mut.interrupt_handle();
         handle.interrupt();
         key_ids.clear();
 
-        let mut new_hk = HotkeyManager::new();
-        new_hk.set_no_repeat(false);
+        let new_hk = HotkeyManager::builder()
+            .no_repeat(self.no_repeat.load(Ordering::SeqCst))
+            .build();
+        self.interrupt = Arc::new(new_hk.interrupt_handle());
+        self.backend_thread = new_hk.backend_thread();
         let new_hkm = Arc::new(Mutex::new(new_hk));
         self.manager = new_hkm.clone();
 
         let hotkey_manager = self.manager.clone();
         let mut hotkey_manager_mut = hotkey_manager.lock().unwrap();
 
-        // Collect hotkeys and their actions upfront
-        for hotkey in hotkeys.values() {
-            let action = hotkey.action.clone();
-            let result = if let Some(action) = action {
-                // Register with an action if present
-                hotkey_manager_mut.register_extrakeys(
-                    hotkey.key,
-                    hotkey.modifiers.as_deref(),
-                    hotkey.extras.as_deref(),
-                    Some(move || {
-                        let action = action.clone();
-                        let action = action.lock().unwrap();
-                        action()
-                    }),
-                )
-            } else {
-                // Register without an action if None
-                hotkey_manager_mut.register_extrakeys(
-                    hotkey.key,
-                    hotkey.modifiers.as_deref(),
-                    hotkey.extras.as_deref(),
-                    None::<fn() -> T>,
-                )
-            };
-
-            match result {
-                Ok(hotkey_id) => key_ids.push(hotkey_id),
-                Err(e) => {
-                    eprintln!("failed to register keybinding {:?}: {}", hotkey.key, e);
+        for (name, hotkey) in hotkeys.iter() {
+            match hotkey.register(&mut hotkey_manager_mut) {
+                Ok(hotkey_id) => {
+                    key_ids.insert(name.clone(), hotkey_id);
                 }
+                Err(e) => self.on_register_error.report(name, hotkey, &e),
             }
         }
 
-        let hkm = hotkey_manager.clone();
-
-        std::thread::spawn(move || {
-            // Lock the Mutex inside the thread, instead of moving the MutexGuard
-            while listening.load(Ordering::SeqCst) {
-                hkm.lock().unwrap().event_loop();
-            }
-        });
+        let listener = self.spawn_listener();
+        *self.listener.lock().unwrap() = Some(listener);
     }
 
     fn start(&self) {
         if self.listening.load(Ordering::SeqCst) {
-            eprintln!("already listening for hotkeys.");
+            log_warn!("already listening for hotkeys.");
             return;
         }
 
+        // Wait for a listener that was stopped from a hotkey action to unregister its hotkeys
+        if let Some(listener) = self.listener.lock().unwrap().take() {
+            let _ = listener.join();
+        }
+
         let hotkey_manager = self.manager.clone();
-        let listening = self.listening.clone();
 
-        listening.store(true, Ordering::SeqCst);
+        self.listening.store(true, Ordering::SeqCst);
 
         // Lock bindings to access keybindings
         let mut hotkey_manager_mut = hotkey_manager.lock().unwrap();
         let hotkeys = self.hotkeys.lock().unwrap();
         let mut key_ids = self.key_ids.lock().unwrap();
 
-        // Collect hotkeys and their actions upfront
-        for hotkey in hotkeys.values() {
-            let action = hotkey.action.clone();
-            let result = if let Some(action) = action {
-                // Register with an action if present
-                hotkey_manager_mut.register_extrakeys(
-                    hotkey.key,
-                    hotkey.modifiers.as_deref(),
-                    hotkey.extras.as_deref(),
-                    Some(move || {
-                        let action = action.clone();
-                        let action = action.lock().unwrap();
-                        action()
-                    }),
-                )
-            } else {
-                // Register without an action if None
-                hotkey_manager_mut.register_extrakeys(
-                    hotkey.key,
-                    hotkey.modifiers.as_deref(),
-                    hotkey.extras.as_deref(),
-                    None::<fn() -> T>,
-                )
-            };
-
-            match result {
-                Ok(hotkey_id) => key_ids.push(hotkey_id),
-                Err(e) => {
-                    eprintln!("failed to register keybinding {:?}: {}", hotkey.key, e);
+        for (name, hotkey) in hotkeys.iter() {
+            match hotkey.register(&mut hotkey_manager_mut) {
+                Ok(hotkey_id) => {
+                    key_ids.insert(name.clone(), hotkey_id);
                 }
+                Err(e) => self.on_register_error.report(name, hotkey, &e),
             }
         }
 
-        let hkm = hotkey_manager.clone();
-
-        std::thread::spawn(move || {
-            // Lock the Mutex inside the thread, instead of moving the MutexGuard
-            while listening.load(Ordering::SeqCst) {
-                hkm.lock().unwrap().event_loop();
-            }
-        });
+        let listener = self.spawn_listener();
+        *self.listener.lock().unwrap() = Some(listener);
     }
 
     fn stop(&self) -> bool {
-        if !self.listening.load(Ordering::SeqCst) {
+        if !self.listening.swap(false, Ordering::SeqCst) {
             return false;
         }
 
-        self.listening.store(false, Ordering::SeqCst);
+        // Interrupt the event loop so the listener thread releases the manager
+        self.interrupt.interrupt();
+
+        // The listener is waiting for the running action, so it can't be joined from there. It
+        // unregisters the hotkeys on its own once the action returned.
+        let current = std::thread::current().id();
+        let mut listener = self.listener.lock().unwrap();
+        let on_listener = listener
+            .as_ref()
+            .is_some_and(|listener| listener.thread().id() == current);
+        if self.backend_thread == Some(current) || on_listener {
+            return true;
+        }
+
+        if let Some(listener) = listener.take() {
+            let _ = listener.join();
+        }
 
         true
     }
+
+    fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::SeqCst)
+    }
+}
+
+/// Sort and dedupe the modifiers so that equivalent combinations compare equal regardless of the
+/// order they were specified in. An empty list is the same as no modifiers, so it becomes `None`.
+fn normalize_modifiers(modifiers: Option<Vec<ModifiersKey>>) -> Option<Vec<ModifiersKey>> {
+    let mut modifiers = modifiers.filter(|modifiers| !modifiers.is_empty())?;
+    modifiers.sort_by_key(|modifier| (modifier.to_mod_code(), *modifier as u8));
+    modifiers.dedup();
+    Some(modifiers)
+}
+
+/// Sort and dedupe the extra keys by their virtual keycode. An empty list becomes `None`.
+fn normalize_extras(extras: Option<Vec<VirtualKey>>) -> Option<Vec<VirtualKey>> {
+    let mut extras = extras.filter(|extras| !extras.is_empty())?;
+    extras.sort_by_key(|extra| extra.to_vk_code());
+    extras.dedup();
+    Some(extras)
 }
 
+/// Error returned when parsing a `GlobalHotkey` from a string. The spans are byte ranges of the
+/// offending token within the parsed string.
 #[derive(Debug)]
 pub enum HotKeyParseError {
-    UnsupportedKey(String),
-    EmptyToken(String),
+    UnsupportedKey {
+        token: String,
+        span: Range<usize>,
+    },
+    EmptyToken {
+        hotkey: String,
+        span: Range<usize>,
+    },
     InvalidFormat(String),
+    /// A key couldn't be created, for example from a char or keycode, see `HotkeyError`
+    Key(HotkeyError),
+}
+
+impl HotKeyParseError {
+    /// Get the byte range of the offending token within the parsed string, if the error is caused
+    /// by a single token.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match *self {
+            HotKeyParseError::UnsupportedKey { ref span, .. }
+            | HotKeyParseError::EmptyToken { ref span, .. } => Some(span.clone()),
+            HotKeyParseError::InvalidFormat(_) | HotKeyParseError::Key(_) => None,
+        }
+    }
 }
 
 impl std::fmt::Display for HotKeyParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
-            HotKeyParseError::UnsupportedKey(ref key) => {
+            HotKeyParseError::UnsupportedKey {
+                ref token,
+                ref span,
+            } => {
                 write!(
                     f,
-                    "Couldn't recognize \"{}\" as a valid key for hotkey",
-                    key
+                    "Couldn't recognize \"{}\" at {}..{} as a valid key for hotkey",
+                    token, span.start, span.end
                 )
             }
-            HotKeyParseError::EmptyToken(ref token) => {
-                write!(f, "Found empty token while parsing hotkey: {}", token)
+            HotKeyParseError::EmptyToken {
+                ref hotkey,
+                ref span,
+            } => {
+                write!(
+                    f,
+                    "Found empty token at {}..{} while parsing hotkey: {}",
+                    span.start, span.end, hotkey
+                )
             }
             HotKeyParseError::InvalidFormat(ref format) => {
                 write!(
                     f,
-                    "Invalid hotkey format: \"{}\", a hotkey should have the modifiers first and only one main key, for example: \"Shift + Alt + K\"",
+                    "Invalid hotkey format: \"{}\", a hotkey should have exactly one main key in addition to the modifiers, for example: \"Shift + Alt + K\"",
                     format
                 )
             }
+            HotKeyParseError::Key(ref err) => write!(f, "Invalid key for hotkey: {}", err),
         }
     }
 }
 
 impl std::error::Error for HotKeyParseError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        // No underlying error, so we return None.
-        None
+        match *self {
+            HotKeyParseError::Key(ref err) => Some(err),
+            // No underlying error, so we return None.
+            _ => None,
+        }
+    }
+}
+
+/// Allows propagating the errors of the key constructors, like `VirtualKey::from_char`, with `?`
+/// in functions that also parse hotkeys.
+impl From<HotkeyError> for HotKeyParseError {
+    fn from(err: HotkeyError) -> Self {
+        HotKeyParseError::Key(err)
     }
 }
 
@@ -298,7 +725,21 @@ impl<T: Send + 'static> TryInto<GlobalHotkey<T>> for &str {
     type Error = HotKeyParseError;
 
     fn try_into(self) -> Result<GlobalHotkey<T>, Self::Error> {
-        let tokens = self.split('+').collect::<Vec<&str>>();
+        // Split into the trimmed tokens and their byte range in the original string
+        let mut offset = 0;
+        let tokens = self
+            .split('+')
+            .map(|raw| {
+                let start = offset + (raw.len() - raw.trim_start().len());
+                let token = raw.trim();
+                offset += raw.len() + 1;
+                (token, start..start + token.len())
+            })
+            .collect::<Vec<_>>();
+        let unsupported = |(token, span): &(&str, Range<usize>)| HotKeyParseError::UnsupportedKey {
+            token: token.to_string(),
+            span: span.clone(),
+        };
         let mut modifiers: Vec<ModifiersKey> = Vec::new();
         let mut key = None;
         let mut extras: Vec<VirtualKey> = Vec::new();
@@ -306,42 +747,36 @@ impl<T: Send + 'static> TryInto<GlobalHotkey<T>> for &str {
         match tokens.len() {
             1 => {
                 // Only a key, no modifiers or extras
-                key = Some(
-                    VirtualKey::try_from(tokens[0].trim())
-                        .map_err(|e| HotKeyParseError::UnsupportedKey(e.to_string()))?,
-                );
+                key = Some(VirtualKey::try_from(tokens[0].0).map_err(|_| unsupported(&tokens[0]))?);
             }
             _ => {
-                let mut found_key = false;
-
-                for raw in tokens {
-                    let token = raw.trim();
+                // Modifiers are recognized in any position. The first other key is the main key,
+                // and all following keys are extras.
+                for entry in &tokens {
+                    let (token, ref span) = *entry;
 
                     if token.is_empty() {
-                        return Err(HotKeyParseError::EmptyToken(self.to_string()));
+                        return Err(HotKeyParseError::EmptyToken {
+                            hotkey: self.to_string(),
+                            span: span.clone(),
+                        });
                     }
 
-                    // If we have already found the key, treat the rest as extras
-                    if found_key {
-                        let extra_key = VirtualKey::try_from(token)
-                            .map_err(|e| HotKeyParseError::UnsupportedKey(e.to_string()))?;
-                        extras.push(extra_key);
+                    // Some modifiers like WIN or NOREPEAT only have a modifier name
+                    if let Ok(modifier) = ModifiersKey::from_keyname(token) {
+                        modifiers.push(modifier);
+                        continue;
+                    }
+
+                    let temp_key = VirtualKey::try_from(token).map_err(|_| unsupported(entry))?;
+
+                    // If the token is a valid modifier, add it to the modifiers
+                    if let Ok(modifier) = temp_key.try_into() {
+                        modifiers.push(modifier);
+                    } else if key.is_none() {
+                        key = Some(temp_key);
                     } else {
-                        if key.is_some() {
-                            return Err(HotKeyParseError::InvalidFormat(self.to_string()));
-                        }
-
-                        let temp_key = VirtualKey::try_from(token)
-                            .map_err(|e| HotKeyParseError::UnsupportedKey(e.to_string()))?;
-
-                        // If the token is a valid modifier, add it to the modifiers
-                        if let Ok(modifier) = temp_key.try_into() {
-                            modifiers.push(modifier);
-                        } else {
-                            // Otherwise, treat it as the main key
-                            key = Some(temp_key);
-                            found_key = true; // Mark that the key has been found
-                        }
+                        extras.push(temp_key);
                     }
                 }
             }
@@ -352,17 +787,226 @@ impl<T: Send + 'static> TryInto<GlobalHotkey<T>> for &str {
 
         Ok(GlobalHotkey {
             key,
-            modifiers: if modifiers.is_empty() {
-                None
-            } else {
-                Some(modifiers)
-            },
-            extras: if extras.is_empty() {
-                None
-            } else {
-                Some(extras)
-            },
+            modifiers: normalize_modifiers(Some(modifiers)),
+            extras: normalize_extras(Some(extras)),
             action: None, // action is still None
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::{Duration, Instant};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_HOTKEY};
+
+    fn parse(hotkey: &str) -> GlobalHotkey<()> {
+        hotkey.try_into().unwrap()
+    }
+
+    #[test]
+    fn modifier_after_main_key_is_a_modifier() {
+        let hotkey = parse("a+ctrl");
+        assert_eq!(hotkey.key, VirtualKey::A);
+        assert_eq!(hotkey.modifiers, Some(vec![ModifiersKey::Ctrl]));
+        assert_eq!(hotkey.extras, None);
+    }
+
+    #[test]
+    fn sided_modifier_key_after_main_key_is_a_modifier() {
+        let hotkey = parse("ctrl+a+lshift");
+        assert_eq!(hotkey.key, VirtualKey::A);
+        assert_eq!(
+            hotkey.modifiers,
+            Some(vec![ModifiersKey::Ctrl, ModifiersKey::Shift])
+        );
+        assert_eq!(hotkey.extras, None);
+    }
+
+    #[test]
+    fn keys_after_main_key_are_extras() {
+        let hotkey = parse("ctrl+a+c+b");
+        assert_eq!(hotkey.key, VirtualKey::A);
+        assert_eq!(hotkey.modifiers, Some(vec![ModifiersKey::Ctrl]));
+        assert_eq!(hotkey.extras, Some(vec![VirtualKey::B, VirtualKey::C]));
+    }
+
+    #[test]
+    fn equality_ignores_order() {
+        assert_eq!(parse("shift+ctrl+a+c+b"), parse("ctrl+a+b+shift+c"));
+        assert_ne!(parse("ctrl+a+b"), parse("ctrl+b+a"));
+    }
+
+    #[test]
+    fn empty_lists_equal_none() {
+        let manager = GlobalHotkeyManager::<()>::new();
+        manager.register_hotkey(
+            "empty".to_string(),
+            VirtualKey::A,
+            Some(vec![]),
+            Some(vec![]),
+            None::<fn()>,
+        );
+        let hotkey = manager.remove_hotkey("empty".to_string()).unwrap();
+        assert_eq!(hotkey.modifiers, None);
+        assert_eq!(hotkey.extras, None);
+        assert_eq!(hotkey, parse("a"));
+    }
+
+    #[test]
+    fn matches_ignores_order() {
+        let hotkey = parse("ctrl+shift+a+b+c");
+        assert!(hotkey.matches(
+            VirtualKey::A,
+            &[ModifiersKey::Shift, ModifiersKey::Ctrl],
+            &[VirtualKey::C, VirtualKey::B]
+        ));
+        assert!(!hotkey.matches(
+            VirtualKey::A,
+            &[ModifiersKey::Ctrl],
+            &[VirtualKey::B, VirtualKey::C]
+        ));
+        assert!(!hotkey.matches(
+            VirtualKey::B,
+            &[ModifiersKey::Ctrl, ModifiersKey::Shift],
+            &[VirtualKey::A, VirtualKey::C]
+        ));
+        assert!(parse("a").matches(VirtualKey::A, &[], &[]));
+    }
+
+    #[test]
+    fn unsupported_key_reports_its_span() {
+        let err = TryInto::<GlobalHotkey<()>>::try_into("ctrl + shft+k").unwrap_err();
+        assert!(matches!(
+            err,
+            HotKeyParseError::UnsupportedKey { ref token, ref span } if token == "shft" && *span == (7..11)
+        ));
+        assert_eq!(err.span(), Some(7..11));
+    }
+
+    #[test]
+    fn empty_token_reports_its_span() {
+        let err = TryInto::<GlobalHotkey<()>>::try_into("ctrl++k").unwrap_err();
+        assert!(matches!(
+            err,
+            HotKeyParseError::EmptyToken { ref hotkey, ref span } if hotkey == "ctrl++k" && *span == (5..5)
+        ));
+    }
+
+    #[test]
+    fn invalid_format_has_no_span() {
+        let err = TryInto::<GlobalHotkey<()>>::try_into("ctrl+shift").unwrap_err();
+        assert!(matches!(err, HotKeyParseError::InvalidFormat(_)));
+        assert_eq!(err.span(), None);
+    }
+
+    #[test]
+    fn key_errors_propagate_into_parse_errors() {
+        fn parse_with_extra(
+            hotkey: &str,
+            extra: &str,
+        ) -> Result<GlobalHotkey<()>, HotKeyParseError> {
+            let mut hotkey: GlobalHotkey<()> = hotkey.try_into()?;
+            let extra = VirtualKey::from_keyname(extra)?;
+            hotkey.extras.get_or_insert_with(Vec::new).push(extra);
+            Ok(hotkey)
+        }
+
+        assert!(parse_with_extra("ctrl+a", "b").is_ok());
+        assert!(matches!(
+            parse_with_extra("ctrl+a", "nokey"),
+            Err(HotKeyParseError::Key(_))
+        ));
+        assert!(matches!(
+            parse_with_extra("ctrl+nokey", "b"),
+            Err(HotKeyParseError::UnsupportedKey { .. })
+        ));
+    }
+
+    #[test]
+    fn register_error_hook_can_be_formatted_while_locked() {
+        let hook = RegisterErrorHook::<()>(Arc::new(Mutex::new(Some(Box::new(|_, _, _| {})))));
+        assert_eq!(
+            format!("{:?}", hook),
+            "Some(Fn(&str, &GlobalHotkey<T>, &HotkeyError))"
+        );
+
+        let _running = hook.0.lock().unwrap();
+        assert_eq!(format!("{:?}", hook), "<locked>");
+    }
+
+    #[test]
+    fn start_stop_start() {
+        let manager = GlobalHotkeyManager::<()>::new();
+        let failures = Arc::new(Mutex::new(0));
+        manager.set_on_register_error({
+            let failures = failures.clone();
+            move |_, _, _| *failures.lock().unwrap() += 1
+        });
+        manager.register_hotkey(
+            "test".to_string(),
+            VirtualKey::F24,
+            Some(vec![
+                ModifiersKey::Ctrl,
+                ModifiersKey::Alt,
+                ModifiersKey::Shift,
+            ]),
+            None,
+            Some(|| {}),
+        );
+
+        for _ in 0..3 {
+            manager.start();
+            assert!(manager.is_listening());
+            assert!(manager.stop());
+            assert!(!manager.is_listening());
+        }
+        assert!(!manager.stop());
+        assert_eq!(*failures.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn shared_hotkeys_run_the_same_arc() {
+        let manager = GlobalHotkeyManager::<()>::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let action: Arc<dyn Fn() + Send + Sync> = Arc::new({
+            let calls = calls.clone();
+            move || {
+                calls.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        let modifiers = vec![ModifiersKey::Ctrl, ModifiersKey::Alt, ModifiersKey::Shift];
+        for (name, key) in [("first", VirtualKey::F23), ("second", VirtualKey::F24)] {
+            manager.register_shared_hotkey(
+                name.to_string(),
+                key,
+                Some(modifiers.clone()),
+                None,
+                action.clone(),
+            );
+        }
+        assert_eq!(Arc::strong_count(&action), 3);
+
+        // Simulate pressing both hotkeys while listening
+        manager.start();
+        for id in manager.key_ids.lock().unwrap().values() {
+            let posted = unsafe { PostMessageW(manager.interrupt.0, WM_HOTKEY, id.0 as usize, 0) };
+            assert_ne!(posted, 0);
+        }
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while calls.load(Ordering::SeqCst) < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(manager.stop());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        for name in ["first", "second"] {
+            let hotkey = manager.remove_hotkey(name.to_string()).unwrap();
+            assert!(matches!(
+                hotkey.action,
+                Some(GlobalHotkeyAction::Shared(ref shared)) if Arc::ptr_eq(shared, &action)
+            ));
+        }
+    }
+}
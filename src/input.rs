@@ -0,0 +1,97 @@
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::SendInput;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::INPUT;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::INPUT_0;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::INPUT_KEYBOARD;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::KEYBDINPUT;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::KEYEVENTF_KEYUP;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::KEYEVENTF_UNICODE;
+
+use crate::error::HotkeyError;
+
+/// Type the given text into the focused application, for example to insert a snippet from a
+/// hotkey callback. The text is sent as unicode characters (`VK_PACKET`), so it doesn't depend on
+/// the keyboard layout. Characters outside of the basic multilingual plane are sent as two
+/// packets, one for each UTF-16 surrogate.
+///
+/// The modifier keys of the hotkey might still be held down while the callback is running, which
+/// can be picked up by the receiving application.
+///
+/// Returns `HotkeyError::SendInputFailed` if windows didn't accept all of the input events, for
+/// example because the input was blocked by another thread or by UIPI.
+///
+/// # Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-sendinput>
+///
+pub fn send_unicode(text: &str) -> Result<(), HotkeyError> {
+    let inputs = unicode_inputs(text);
+    if inputs.is_empty() {
+        return Ok(());
+    }
+
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        )
+    };
+
+    if sent as usize == inputs.len() {
+        Ok(())
+    } else {
+        Err(HotkeyError::SendInputFailed)
+    }
+}
+
+/// Create a key down and key up event for each UTF-16 code unit of the text.
+///
+fn unicode_inputs(text: &str) -> Vec<INPUT> {
+    text.encode_utf16()
+        .flat_map(|unit| {
+            [0, KEYEVENTF_KEYUP].map(|flags| INPUT {
+                r#type: INPUT_KEYBOARD,
+                Anonymous: INPUT_0 {
+                    ki: KEYBDINPUT {
+                        wVk: 0,
+                        wScan: unit,
+                        dwFlags: KEYEVENTF_UNICODE | flags,
+                        time: 0,
+                        dwExtraInfo: 0,
+                    },
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surrogate_pair_is_sent_as_two_packets() {
+        let inputs = unicode_inputs("\u{1F600}");
+        assert_eq!(inputs.len(), 4);
+
+        let events: Vec<_> = inputs
+            .iter()
+            .map(|input| {
+                assert_eq!(input.r#type, INPUT_KEYBOARD);
+                let ki = unsafe { input.Anonymous.ki };
+                assert_eq!(ki.wVk, 0);
+                assert_ne!(ki.dwFlags & KEYEVENTF_UNICODE, 0);
+                (ki.wScan, ki.dwFlags & KEYEVENTF_KEYUP != 0)
+            })
+            .collect();
+        // Each surrogate is pressed and released before the next one
+        assert_eq!(
+            events,
+            vec![
+                (0xD83D, false),
+                (0xD83D, true),
+                (0xDE00, false),
+                (0xDE00, true)
+            ]
+        );
+    }
+}
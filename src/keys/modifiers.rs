@@ -6,12 +6,35 @@ use std::fmt::Display;
 ///
 /// See: `fsModifiers` from <https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
 ///
+/// ## Left and right modifiers
+/// `RegisterHotKey` can't distinguish between the left and right variants of a modifier. The
+/// sided modifiers (like `ModifiersKey::RShift`) are registered with the regular modifier code,
+/// and the matching `VirtualKey` (like `VirtualKey::RShift`) is added to the extra keys. So this is
+/// only enforced by the extra key check after the hotkey event is fired, on a best-effort basis.
+/// For example, holding both shift keys will still trigger a `RShift` hotkey.
+///
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ModifiersKey {
     Alt,
     Ctrl,
     Shift,
     Win,
+    /// Left ALT key, see [Left and right modifiers](#left-and-right-modifiers)
+    LAlt,
+    /// Right ALT key, see [Left and right modifiers](#left-and-right-modifiers)
+    RAlt,
+    /// Left CTRL key, see [Left and right modifiers](#left-and-right-modifiers)
+    LCtrl,
+    /// Right CTRL key, see [Left and right modifiers](#left-and-right-modifiers)
+    RCtrl,
+    /// Left SHIFT key, see [Left and right modifiers](#left-and-right-modifiers)
+    LShift,
+    /// Right SHIFT key, see [Left and right modifiers](#left-and-right-modifiers)
+    RShift,
+    /// Left Windows key, see [Left and right modifiers](#left-and-right-modifiers)
+    LWin,
+    /// Right Windows key, see [Left and right modifiers](#left-and-right-modifiers)
+    RWin,
     /// This is a virtual modifier key that is used to prevent automatically repeating triggers
     /// when the hotkey is being held down. When converting to a VirtualKey, this is mapped to KeyCode 0
     NoRepeat,
@@ -33,14 +56,30 @@ impl ModifiersKey {
     /// - CTRL / CONTROL
     /// - SHIFT
     /// - WIN / WINDOWS / SUPER
+    /// - LEFTALT, RIGHTALT
+    /// - LEFTCTRL / LEFTCONTROL, RIGHTCTRL / RIGHTCONTROL
+    /// - LEFTSHIFT, RIGHTSHIFT
+    /// - LEFTWIN, RIGHTWIN
     /// - NOREPEAT / NO_REPEAT
     ///
+    /// The short key names like `LSHIFT` or `LWIN` are not modifier names. They are parsed as the
+    /// `VirtualKey` of the same name, which converts to the modifier for either side, like
+    /// `ModifiersKey::Shift`.
+    ///
     pub fn from_keyname(val: &str) -> Result<Self, HotkeyError> {
         Ok(match val.to_ascii_uppercase().as_ref() {
             "ALT" => ModifiersKey::Alt,
             "CTRL" | "CONTROL" => ModifiersKey::Ctrl,
             "SHIFT" => ModifiersKey::Shift,
             "WIN" | "WINDOWS" | "SUPER" => ModifiersKey::Win,
+            "LEFTALT" => ModifiersKey::LAlt,
+            "RIGHTALT" => ModifiersKey::RAlt,
+            "LEFTCTRL" | "LEFTCONTROL" => ModifiersKey::LCtrl,
+            "RIGHTCTRL" | "RIGHTCONTROL" => ModifiersKey::RCtrl,
+            "LEFTSHIFT" => ModifiersKey::LShift,
+            "RIGHTSHIFT" => ModifiersKey::RShift,
+            "LEFTWIN" => ModifiersKey::LWin,
+            "RIGHTWIN" => ModifiersKey::RWin,
             "NOREPEAT" | "NO_REPEAT" => ModifiersKey::NoRepeat,
             "NON" => ModifiersKey::Non,
             val => return Err(HotkeyError::InvalidKey(val.to_string())),
@@ -55,10 +94,10 @@ impl ModifiersKey {
         use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
 
         match self {
-            ModifiersKey::Alt => MOD_ALT,
-            ModifiersKey::Ctrl => MOD_CONTROL,
-            ModifiersKey::Shift => MOD_SHIFT,
-            ModifiersKey::Win => MOD_WIN,
+            ModifiersKey::Alt | ModifiersKey::LAlt | ModifiersKey::RAlt => MOD_ALT,
+            ModifiersKey::Ctrl | ModifiersKey::LCtrl | ModifiersKey::RCtrl => MOD_CONTROL,
+            ModifiersKey::Shift | ModifiersKey::LShift | ModifiersKey::RShift => MOD_SHIFT,
+            ModifiersKey::Win | ModifiersKey::LWin | ModifiersKey::RWin => MOD_WIN,
             ModifiersKey::NoRepeat => MOD_NOREPEAT,
             ModifiersKey::Non => 0,
         }
@@ -73,6 +112,27 @@ impl ModifiersKey {
             ModifiersKey::Non.to_mod_code()
         }
     }
+
+    /// Get the extra keys that need to be checked in addition to the modifier code, for the
+    /// modifiers that `RegisterHotKey` can't express on its own.
+    ///
+    #[cfg(windows)]
+    pub(crate) fn extra_keys(keys: Option<&[ModifiersKey]>) -> Vec<VirtualKey> {
+        keys.unwrap_or_default()
+            .iter()
+            .filter_map(|key| match key {
+                ModifiersKey::LAlt => Some(VirtualKey::LMenu),
+                ModifiersKey::RAlt => Some(VirtualKey::RMenu),
+                ModifiersKey::LCtrl => Some(VirtualKey::LControl),
+                ModifiersKey::RCtrl => Some(VirtualKey::RControl),
+                ModifiersKey::LShift => Some(VirtualKey::LShift),
+                ModifiersKey::RShift => Some(VirtualKey::RShift),
+                ModifiersKey::LWin => Some(VirtualKey::LWin),
+                ModifiersKey::RWin => Some(VirtualKey::RWin),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl Display for ModifiersKey {
@@ -82,6 +142,14 @@ impl Display for ModifiersKey {
             ModifiersKey::Ctrl => "CONTROL",
             ModifiersKey::Shift => "SHIFT",
             ModifiersKey::Win => "WIN",
+            ModifiersKey::LAlt => "LEFTALT",
+            ModifiersKey::RAlt => "RIGHTALT",
+            ModifiersKey::LCtrl => "LEFTCONTROL",
+            ModifiersKey::RCtrl => "RIGHTCONTROL",
+            ModifiersKey::LShift => "LEFTSHIFT",
+            ModifiersKey::RShift => "RIGHTSHIFT",
+            ModifiersKey::LWin => "LEFTWIN",
+            ModifiersKey::RWin => "RIGHTWIN",
             ModifiersKey::NoRepeat => "NO_REPEAT",
             ModifiersKey::Non => "NON",
         };
@@ -95,8 +163,47 @@ impl From<ModifiersKey> for VirtualKey {
             ModifiersKey::Alt => VirtualKey::Menu,
             ModifiersKey::Ctrl => VirtualKey::Control,
             ModifiersKey::Shift => VirtualKey::Shift,
-            ModifiersKey::Win => VirtualKey::LWin,
+            ModifiersKey::LAlt => VirtualKey::LMenu,
+            ModifiersKey::RAlt => VirtualKey::RMenu,
+            ModifiersKey::LCtrl => VirtualKey::LControl,
+            ModifiersKey::RCtrl => VirtualKey::RControl,
+            ModifiersKey::LShift => VirtualKey::LShift,
+            ModifiersKey::RShift => VirtualKey::RShift,
+            ModifiersKey::Win | ModifiersKey::LWin => VirtualKey::LWin,
+            ModifiersKey::RWin => VirtualKey::RWin,
             ModifiersKey::NoRepeat | ModifiersKey::Non => VirtualKey::CustomKeyCode(0),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_sided_names_match_either_side() {
+        assert!(ModifiersKey::from_keyname("lshift").is_err());
+        assert_eq!(VirtualKey::LShift.try_into(), Ok(ModifiersKey::Shift));
+        assert_eq!(VirtualKey::RWin.try_into(), Ok(ModifiersKey::Win));
+        assert_eq!(VirtualKey::LControl.try_into(), Ok(ModifiersKey::Ctrl));
+    }
+
+    #[test]
+    fn sided_modifier_names_round_trip() {
+        for modifier in [
+            ModifiersKey::LAlt,
+            ModifiersKey::RAlt,
+            ModifiersKey::LCtrl,
+            ModifiersKey::RCtrl,
+            ModifiersKey::LShift,
+            ModifiersKey::RShift,
+            ModifiersKey::LWin,
+            ModifiersKey::RWin,
+        ] {
+            assert_eq!(
+                ModifiersKey::from_keyname(&modifier.to_string()),
+                Ok(modifier)
+            );
+        }
+    }
+}
@@ -340,6 +340,9 @@ pub enum VirtualKey {
     ///
     /// See: <https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes>
     ///
+    /// The keycode is not validated, prefer `VirtualKey::custom` which checks that it is in the
+    /// valid range. Registering a hotkey with a keycode above `0xFF` fails.
+    ///
     CustomKeyCode(u16),
 }
 
@@ -353,20 +356,44 @@ impl TryFrom<&str> for VirtualKey {
 impl TryFrom<char> for VirtualKey {
     type Error = HotkeyError;
     fn try_from(ch: char) -> Result<Self, Self::Error> {
-        match ch.to_ascii_uppercase() {
-            ch @ ('A'..='Z' | '0'..='9') => Ok(Self::CustomKeyCode(ch as u16)),
-            ch => Err(HotkeyError::InvalidKeyChar(ch)),
-        }
+        Self::from_char(ch)
     }
 }
 
 impl VirtualKey {
-    /// Try to create a VirtualKey from a char. This only works for the simple number and letter keys
-    /// ('A' to 'Z' and '0' to '9'). Letters can be upper or lower case
+    /// Create a VirtualKey for an arbitrary keycode, like `VirtualKey::CustomKeyCode`, but check
+    /// that the keycode is in the valid range of `0x01` to `0xFE`.
+    ///
+    /// See: <https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes>
+    ///
+    pub const fn custom(code: u16) -> Result<Self, HotkeyError> {
+        match code {
+            0x01..=0xFE => Ok(Self::CustomKeyCode(code)),
+            code => Err(HotkeyError::InvalidKeyCode(code)),
+        }
+    }
+
+    /// Try to create a VirtualKey from a char. This works for the simple number and letter keys
+    /// ('A' to 'Z' and '0' to '9') and the punctuation keys. Letters can be upper or lower case
+    ///
+    /// Punctuation is mapped to the OEM key that produces it on the US layout, with or without
+    /// SHIFT, so both `'-'` and `'_'` return `VirtualKey::Minus`. On other layouts, the key might
+    /// produce a different char.
     ///
     pub const fn from_char(ch: char) -> Result<Self, HotkeyError> {
         match ch.to_ascii_uppercase() {
             ch @ ('A'..='Z' | '0'..='9') => Ok(Self::CustomKeyCode(ch as u16)),
+            ';' | ':' => Ok(Self::Semicolon),
+            '=' | '+' => Ok(Self::Plus),
+            ',' | '<' => Ok(Self::Comma),
+            '-' | '_' => Ok(Self::Minus),
+            '.' | '>' => Ok(Self::Period),
+            '/' | '?' => Ok(Self::Slash),
+            '`' | '~' => Ok(Self::Backquote),
+            '[' | '{' => Ok(Self::BracketLeft),
+            '\\' | '|' => Ok(Self::Backslash),
+            ']' | '}' => Ok(Self::BracketRight),
+            '\'' | '"' => Ok(Self::Quote),
             ch => Err(HotkeyError::InvalidKeyChar(ch)),
         }
     }
@@ -534,6 +561,135 @@ impl VirtualKey {
         }
     }
 
+    /// Check if the key is one of the OEM keys, whose virtual keycode depends on the keyboard
+    /// layout. The same physical key can have a different keycode in another layout.
+    ///
+    pub const fn is_layout_dependent(&self) -> bool {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+        matches!(
+            self.to_vk_code(),
+            VK_OEM_1
+                | VK_OEM_PLUS
+                | VK_OEM_COMMA
+                | VK_OEM_MINUS
+                | VK_OEM_PERIOD
+                | VK_OEM_2
+                | VK_OEM_3
+                | VK_OEM_4
+                | VK_OEM_5
+                | VK_OEM_6
+                | VK_OEM_7
+                | VK_OEM_8
+                | VK_OEM_102
+        )
+    }
+
+    /// Check if the key is one of the modifier keys (Shift, Ctrl, Alt or Win), either generic or
+    /// sided.
+    ///
+    pub const fn is_modifier(&self) -> bool {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+        matches!(
+            self.to_vk_code(),
+            VK_SHIFT
+                | VK_CONTROL
+                | VK_MENU
+                | VK_LSHIFT
+                | VK_RSHIFT
+                | VK_LCONTROL
+                | VK_RCONTROL
+                | VK_LMENU
+                | VK_RMENU
+                | VK_LWIN
+                | VK_RWIN
+        )
+    }
+
+    /// Check if the key is one of the lock keys CAPS LOCK, NUM LOCK or SCROLL LOCK, which have a
+    /// toggle state in addition to being pressed.
+    ///
+    pub const fn is_lock_key(&self) -> bool {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+        matches!(self.to_vk_code(), VK_CAPITAL | VK_NUMLOCK | VK_SCROLL)
+    }
+
+    /// Check if the key is used for regular typing, meaning letters, digits (including the numpad),
+    /// space and the OEM punctuation keys. Registering one of these keys as a hotkey without any
+    /// modifiers makes it impossible to type the key in other applications.
+    ///
+    pub const fn is_typing_key(&self) -> bool {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+        matches!(
+            self.to_vk_code(),
+            0x30..=0x39 | 0x41..=0x5A | VK_SPACE | VK_NUMPAD0..=VK_NUMPAD9
+        ) || self.is_layout_dependent()
+    }
+
+    /// Get a short, human-readable label for the key that can be shown in a UI, like `Esc` or
+    /// `Space`. Letters and digits are returned as is. Keys without a short label fall back to the
+    /// name used by `Display`, and `"Unknown"` is returned for keycodes that windows doesn't
+    /// define.
+    ///
+    /// The labels don't depend on the keyboard layout or language.
+    ///
+    pub fn short_name(&self) -> &'static str {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+
+        const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        const DIGITS: &str = "0123456789";
+        const NUMPAD: [&str; 10] = [
+            "Num 0", "Num 1", "Num 2", "Num 3", "Num 4", "Num 5", "Num 6", "Num 7", "Num 8",
+            "Num 9",
+        ];
+        const FUNCTION: [&str; 24] = [
+            "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "F13",
+            "F14", "F15", "F16", "F17", "F18", "F19", "F20", "F21", "F22", "F23", "F24",
+        ];
+
+        let code = self.to_vk_code();
+        match code {
+            0x41..=0x5A => {
+                let i = (code - 0x41) as usize;
+                &LETTERS[i..i + 1]
+            }
+            0x30..=0x39 => {
+                let i = (code - 0x30) as usize;
+                &DIGITS[i..i + 1]
+            }
+            VK_NUMPAD0..=VK_NUMPAD9 => NUMPAD[(code - VK_NUMPAD0) as usize],
+            VK_F1..=VK_F24 => FUNCTION[(code - VK_F1) as usize],
+            VK_SPACE => "Space",
+            VK_TAB => "Tab",
+            VK_RETURN => "Enter",
+            VK_ESCAPE => "Esc",
+            VK_BACK => "Backspace",
+            VK_DELETE => "Del",
+            VK_INSERT => "Ins",
+            VK_HOME => "Home",
+            VK_END => "End",
+            VK_PRIOR => "PgUp",
+            VK_NEXT => "PgDn",
+            VK_LEFT => "Left",
+            VK_UP => "Up",
+            VK_RIGHT => "Right",
+            VK_DOWN => "Down",
+            VK_SNAPSHOT => "PrtSc",
+            VK_PAUSE => "Pause",
+            VK_CAPITAL => "CapsLock",
+            VK_NUMLOCK => "NumLock",
+            VK_SCROLL => "ScrollLock",
+            VK_SHIFT | VK_LSHIFT | VK_RSHIFT => "Shift",
+            VK_CONTROL | VK_LCONTROL | VK_RCONTROL => "Ctrl",
+            VK_MENU | VK_LMENU | VK_RMENU => "Alt",
+            VK_LWIN | VK_RWIN => "Win",
+            VK_APPS => "Menu",
+            _ => match vk_const_name(code) {
+                Some(name) => name,
+                None => "Unknown",
+            },
+        }
+    }
+
     /// Take in a string and try to guess what Virtual Key (VK) it is meant to represent.
     /// Returns the VK code as u16 on success (a key representation was recognized).
     ///
@@ -544,13 +700,16 @@ impl VirtualKey {
     /// - Any other key can be represented by directly specifying the VK keycode value in 2
     /// digit hex representation. For example 0x08 == VK_TAB (Tab key)
     ///
+    /// Note that `MENU` is the Alt key, following the name of `VK_MENU`. The context menu key is
+    /// called `APPS` or `CONTEXTMENU`.
+    ///
     /// See <https://docs.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes>
     ///
     pub fn from_keyname(val: &str) -> Result<Self, HotkeyError> {
         let val = val.to_ascii_uppercase();
 
         // Single letter => Simply use the ASCII Code
-        if val.as_bytes().len() == 1 {
+        if val.len() == 1 {
             let val = val.as_bytes()[0];
             if val.is_ascii_uppercase() || val.is_ascii_digit() {
                 return Ok(Self::CustomKeyCode(val as u16));
@@ -560,7 +719,7 @@ impl VirtualKey {
         // 1 byte hex code => Use the raw keycode value
         if val.len() >= 3 && val.len() <= 6 && val.starts_with("0x") || val.starts_with("0X") {
             if let Ok(val) = u16::from_str_radix(&val[2..], 16) {
-                return Ok(Self::CustomKeyCode(val));
+                return Self::custom(val);
             } else {
                 return Err(HotkeyError::InvalidKey(val));
             }
@@ -596,7 +755,7 @@ impl VirtualKey {
             "HELP" => Self::Help,
             "LWIN" => Self::LWin,
             "RWIN" => Self::RWin,
-            "APPS" => Self::Apps,
+            "APPS" | "CONTEXTMENU" => Self::Apps,
             "SLEEP" => Self::Sleep,
             "NUMPAD0" | "NUM0" => Self::Numpad0,
             "NUMPAD1" | "NUM1" => Self::Numpad1,
@@ -692,8 +851,6 @@ impl VirtualKey {
 
 impl Display for VirtualKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
-
         let code = self.to_vk_code();
 
         if code >= 'A' as u16 && code <= 'Z' as u16 {
@@ -704,127 +861,138 @@ impl Display for VirtualKey {
             return write!(f, "{}", code as u8 as char);
         }
 
-        let val = match code {
-            VK_BACK => "VK_BACK",
-            VK_TAB => "VK_TAB",
-            VK_CLEAR => "VK_CLEAR",
-            VK_RETURN => "VK_RETURN",
-            VK_SHIFT => "VK_SHIFT",
-            VK_CONTROL => "VK_CONTROL",
-            VK_MENU => "VK_MENU",
-            VK_PAUSE => "VK_PAUSE",
-            VK_CAPITAL => "VK_CAPITAL",
-            VK_ESCAPE => "VK_ESCAPE",
-            VK_SPACE => "VK_SPACE",
-            VK_PRIOR => "VK_PRIOR",
-            VK_NEXT => "VK_NEXT",
-            VK_END => "VK_END",
-            VK_HOME => "VK_HOME",
-            VK_LEFT => "VK_LEFT",
-            VK_UP => "VK_UP",
-            VK_RIGHT => "VK_RIGHT",
-            VK_DOWN => "VK_DOWN",
-            VK_SELECT => "VK_SELECT",
-            VK_PRINT => "VK_PRINT",
-            VK_EXECUTE => "VK_EXECUTE",
-            VK_SNAPSHOT => "VK_SNAPSHOT",
-            VK_INSERT => "VK_INSERT",
-            VK_DELETE => "VK_DELETE",
-            VK_HELP => "VK_HELP",
-            VK_LWIN => "VK_LWIN",
-            VK_RWIN => "VK_RWIN",
-            VK_APPS => "VK_APPS",
-            VK_SLEEP => "VK_SLEEP",
-            VK_NUMPAD0 => "VK_NUMPAD0",
-            VK_NUMPAD1 => "VK_NUMPAD1",
-            VK_NUMPAD2 => "VK_NUMPAD2",
-            VK_NUMPAD3 => "VK_NUMPAD3",
-            VK_NUMPAD4 => "VK_NUMPAD4",
-            VK_NUMPAD5 => "VK_NUMPAD5",
-            VK_NUMPAD6 => "VK_NUMPAD6",
-            VK_NUMPAD7 => "VK_NUMPAD7",
-            VK_NUMPAD8 => "VK_NUMPAD8",
-            VK_NUMPAD9 => "VK_NUMPAD9",
-            VK_MULTIPLY => "VK_MULTIPLY",
-            VK_ADD => "VK_ADD",
-            VK_SEPARATOR => "VK_SEPARATOR",
-            VK_SUBTRACT => "VK_SUBTRACT",
-            VK_DECIMAL => "VK_DECIMAL",
-            VK_DIVIDE => "VK_DIVIDE",
-            VK_F1 => "VK_F1",
-            VK_F2 => "VK_F2",
-            VK_F3 => "VK_F3",
-            VK_F4 => "VK_F4",
-            VK_F5 => "VK_F5",
-            VK_F6 => "VK_F6",
-            VK_F7 => "VK_F7",
-            VK_F8 => "VK_F8",
-            VK_F9 => "VK_F9",
-            VK_F10 => "VK_F10",
-            VK_F11 => "VK_F11",
-            VK_F12 => "VK_F12",
-            VK_F13 => "VK_F13",
-            VK_F14 => "VK_F14",
-            VK_F15 => "VK_F15",
-            VK_F16 => "VK_F16",
-            VK_F17 => "VK_F17",
-            VK_F18 => "VK_F18",
-            VK_F19 => "VK_F19",
-            VK_F20 => "VK_F20",
-            VK_F21 => "VK_F21",
-            VK_F22 => "VK_F22",
-            VK_F23 => "VK_F23",
-            VK_F24 => "VK_F24",
-            VK_NUMLOCK => "VK_NUMLOCK",
-            VK_SCROLL => "VK_SCROLL",
-            VK_LSHIFT => "VK_LSHIFT",
-            VK_RSHIFT => "VK_RSHIFT",
-            VK_LCONTROL => "VK_LCONTROL",
-            VK_RCONTROL => "VK_RCONTROL",
-            VK_LMENU => "VK_LMENU",
-            VK_RMENU => "VK_RMENU",
-            VK_BROWSER_BACK => "VK_BROWSER_BACK",
-            VK_BROWSER_FORWARD => "VK_BROWSER_FORWARD",
-            VK_BROWSER_REFRESH => "VK_BROWSER_REFRESH",
-            VK_BROWSER_STOP => "VK_BROWSER_STOP",
-            VK_BROWSER_SEARCH => "VK_BROWSER_SEARCH",
-            VK_BROWSER_FAVORITES => "VK_BROWSER_FAVORITES",
-            VK_BROWSER_HOME => "VK_BROWSER_HOME",
-            VK_VOLUME_MUTE => "VK_VOLUME_MUTE",
-            VK_VOLUME_DOWN => "VK_VOLUME_DOWN",
-            VK_VOLUME_UP => "VK_VOLUME_UP",
-            VK_MEDIA_NEXT_TRACK => "VK_MEDIA_NEXT_TRACK",
-            VK_MEDIA_PREV_TRACK => "VK_MEDIA_PREV_TRACK",
-            VK_MEDIA_STOP => "VK_MEDIA_STOP",
-            VK_MEDIA_PLAY_PAUSE => "VK_MEDIA_PLAY_PAUSE",
-            VK_LAUNCH_MAIL => "VK_LAUNCH_MAIL",
-            VK_LAUNCH_MEDIA_SELECT => "VK_LAUNCH_MEDIA_SELECT",
-            VK_LAUNCH_APP1 => "VK_LAUNCH_APP1",
-            VK_LAUNCH_APP2 => "VK_LAUNCH_APP2",
-            VK_OEM_1 => "VK_OEM_1",
-            VK_OEM_PLUS => "VK_OEM_PLUS",
-            VK_OEM_COMMA => "VK_OEM_COMMA",
-            VK_OEM_MINUS => "VK_OEM_MINUS",
-            VK_OEM_PERIOD => "VK_OEM_PERIOD",
-            VK_OEM_2 => "VK_OEM_2",
-            VK_OEM_3 => "VK_OEM_3",
-            VK_OEM_4 => "VK_OEM_4",
-            VK_OEM_5 => "VK_OEM_5",
-            VK_OEM_6 => "VK_OEM_6",
-            VK_OEM_7 => "VK_OEM_7",
-            VK_OEM_8 => "VK_OEM_8",
-            VK_OEM_102 => "VK_OEM_102",
-            VK_ATTN => "VK_ATTN",
-            VK_CRSEL => "VK_CRSEL",
-            VK_EXSEL => "VK_EXSEL",
-            VK_PLAY => "VK_PLAY",
-            VK_ZOOM => "VK_ZOOM",
-            VK_PA1 => "VK_PA1",
-            VK_OEM_CLEAR => "VK_OEM_CLEAR",
-            vk_code => return write!(f, "0x{:x}", vk_code),
-        };
-
-        write!(f, "{}", val)
+        match vk_const_name(code) {
+            Some(val) => write!(f, "{}", val),
+            None => write!(f, "0x{:x}", code),
+        }
+    }
+}
+
+/// Get the name of the windows constant for a virtual keycode, like `VK_RETURN`. This is used by
+/// `Display`, and by `short_name` as the fallback for keys without a short label, so both share
+/// one table instead of keeping two copies of it in sync.
+///
+const fn vk_const_name(code: u16) -> Option<&'static str> {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+
+    match code {
+        VK_BACK => Some("VK_BACK"),
+        VK_TAB => Some("VK_TAB"),
+        VK_CLEAR => Some("VK_CLEAR"),
+        VK_RETURN => Some("VK_RETURN"),
+        VK_SHIFT => Some("VK_SHIFT"),
+        VK_CONTROL => Some("VK_CONTROL"),
+        VK_MENU => Some("VK_MENU"),
+        VK_PAUSE => Some("VK_PAUSE"),
+        VK_CAPITAL => Some("VK_CAPITAL"),
+        VK_ESCAPE => Some("VK_ESCAPE"),
+        VK_SPACE => Some("VK_SPACE"),
+        VK_PRIOR => Some("VK_PRIOR"),
+        VK_NEXT => Some("VK_NEXT"),
+        VK_END => Some("VK_END"),
+        VK_HOME => Some("VK_HOME"),
+        VK_LEFT => Some("VK_LEFT"),
+        VK_UP => Some("VK_UP"),
+        VK_RIGHT => Some("VK_RIGHT"),
+        VK_DOWN => Some("VK_DOWN"),
+        VK_SELECT => Some("VK_SELECT"),
+        VK_PRINT => Some("VK_PRINT"),
+        VK_EXECUTE => Some("VK_EXECUTE"),
+        VK_SNAPSHOT => Some("VK_SNAPSHOT"),
+        VK_INSERT => Some("VK_INSERT"),
+        VK_DELETE => Some("VK_DELETE"),
+        VK_HELP => Some("VK_HELP"),
+        VK_LWIN => Some("VK_LWIN"),
+        VK_RWIN => Some("VK_RWIN"),
+        VK_APPS => Some("VK_APPS"),
+        VK_SLEEP => Some("VK_SLEEP"),
+        VK_NUMPAD0 => Some("VK_NUMPAD0"),
+        VK_NUMPAD1 => Some("VK_NUMPAD1"),
+        VK_NUMPAD2 => Some("VK_NUMPAD2"),
+        VK_NUMPAD3 => Some("VK_NUMPAD3"),
+        VK_NUMPAD4 => Some("VK_NUMPAD4"),
+        VK_NUMPAD5 => Some("VK_NUMPAD5"),
+        VK_NUMPAD6 => Some("VK_NUMPAD6"),
+        VK_NUMPAD7 => Some("VK_NUMPAD7"),
+        VK_NUMPAD8 => Some("VK_NUMPAD8"),
+        VK_NUMPAD9 => Some("VK_NUMPAD9"),
+        VK_MULTIPLY => Some("VK_MULTIPLY"),
+        VK_ADD => Some("VK_ADD"),
+        VK_SEPARATOR => Some("VK_SEPARATOR"),
+        VK_SUBTRACT => Some("VK_SUBTRACT"),
+        VK_DECIMAL => Some("VK_DECIMAL"),
+        VK_DIVIDE => Some("VK_DIVIDE"),
+        VK_F1 => Some("VK_F1"),
+        VK_F2 => Some("VK_F2"),
+        VK_F3 => Some("VK_F3"),
+        VK_F4 => Some("VK_F4"),
+        VK_F5 => Some("VK_F5"),
+        VK_F6 => Some("VK_F6"),
+        VK_F7 => Some("VK_F7"),
+        VK_F8 => Some("VK_F8"),
+        VK_F9 => Some("VK_F9"),
+        VK_F10 => Some("VK_F10"),
+        VK_F11 => Some("VK_F11"),
+        VK_F12 => Some("VK_F12"),
+        VK_F13 => Some("VK_F13"),
+        VK_F14 => Some("VK_F14"),
+        VK_F15 => Some("VK_F15"),
+        VK_F16 => Some("VK_F16"),
+        VK_F17 => Some("VK_F17"),
+        VK_F18 => Some("VK_F18"),
+        VK_F19 => Some("VK_F19"),
+        VK_F20 => Some("VK_F20"),
+        VK_F21 => Some("VK_F21"),
+        VK_F22 => Some("VK_F22"),
+        VK_F23 => Some("VK_F23"),
+        VK_F24 => Some("VK_F24"),
+        VK_NUMLOCK => Some("VK_NUMLOCK"),
+        VK_SCROLL => Some("VK_SCROLL"),
+        VK_LSHIFT => Some("VK_LSHIFT"),
+        VK_RSHIFT => Some("VK_RSHIFT"),
+        VK_LCONTROL => Some("VK_LCONTROL"),
+        VK_RCONTROL => Some("VK_RCONTROL"),
+        VK_LMENU => Some("VK_LMENU"),
+        VK_RMENU => Some("VK_RMENU"),
+        VK_BROWSER_BACK => Some("VK_BROWSER_BACK"),
+        VK_BROWSER_FORWARD => Some("VK_BROWSER_FORWARD"),
+        VK_BROWSER_REFRESH => Some("VK_BROWSER_REFRESH"),
+        VK_BROWSER_STOP => Some("VK_BROWSER_STOP"),
+        VK_BROWSER_SEARCH => Some("VK_BROWSER_SEARCH"),
+        VK_BROWSER_FAVORITES => Some("VK_BROWSER_FAVORITES"),
+        VK_BROWSER_HOME => Some("VK_BROWSER_HOME"),
+        VK_VOLUME_MUTE => Some("VK_VOLUME_MUTE"),
+        VK_VOLUME_DOWN => Some("VK_VOLUME_DOWN"),
+        VK_VOLUME_UP => Some("VK_VOLUME_UP"),
+        VK_MEDIA_NEXT_TRACK => Some("VK_MEDIA_NEXT_TRACK"),
+        VK_MEDIA_PREV_TRACK => Some("VK_MEDIA_PREV_TRACK"),
+        VK_MEDIA_STOP => Some("VK_MEDIA_STOP"),
+        VK_MEDIA_PLAY_PAUSE => Some("VK_MEDIA_PLAY_PAUSE"),
+        VK_LAUNCH_MAIL => Some("VK_LAUNCH_MAIL"),
+        VK_LAUNCH_MEDIA_SELECT => Some("VK_LAUNCH_MEDIA_SELECT"),
+        VK_LAUNCH_APP1 => Some("VK_LAUNCH_APP1"),
+        VK_LAUNCH_APP2 => Some("VK_LAUNCH_APP2"),
+        VK_OEM_1 => Some("VK_OEM_1"),
+        VK_OEM_PLUS => Some("VK_OEM_PLUS"),
+        VK_OEM_COMMA => Some("VK_OEM_COMMA"),
+        VK_OEM_MINUS => Some("VK_OEM_MINUS"),
+        VK_OEM_PERIOD => Some("VK_OEM_PERIOD"),
+        VK_OEM_2 => Some("VK_OEM_2"),
+        VK_OEM_3 => Some("VK_OEM_3"),
+        VK_OEM_4 => Some("VK_OEM_4"),
+        VK_OEM_5 => Some("VK_OEM_5"),
+        VK_OEM_6 => Some("VK_OEM_6"),
+        VK_OEM_7 => Some("VK_OEM_7"),
+        VK_OEM_8 => Some("VK_OEM_8"),
+        VK_OEM_102 => Some("VK_OEM_102"),
+        VK_ATTN => Some("VK_ATTN"),
+        VK_CRSEL => Some("VK_CRSEL"),
+        VK_EXSEL => Some("VK_EXSEL"),
+        VK_PLAY => Some("VK_PLAY"),
+        VK_ZOOM => Some("VK_ZOOM"),
+        VK_PA1 => Some("VK_PA1"),
+        VK_OEM_CLEAR => Some("VK_OEM_CLEAR"),
+        _ => None,
     }
 }
 
@@ -849,6 +1017,8 @@ impl TryInto<ModifiersKey> for VirtualKey {
         use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
 
         Ok(match self.to_vk_code() {
+            // The sided keys match either side, use the `LEFT`/`RIGHT` modifier names to match
+            // one side only
             VK_MENU | VK_LMENU | VK_RMENU => ModifiersKey::Alt,
             VK_CONTROL | VK_LCONTROL | VK_RCONTROL => ModifiersKey::Ctrl,
             VK_SHIFT | VK_LSHIFT | VK_RSHIFT => ModifiersKey::Shift,
@@ -857,3 +1027,59 @@ impl TryInto<ModifiersKey> for VirtualKey {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        VK_APPS, VK_MENU, VK_OEM_1, VK_OEM_2, VK_OEM_7, VK_OEM_COMMA, VK_OEM_MINUS,
+    };
+
+    #[test]
+    fn context_menu_names_are_the_apps_key() {
+        for name in ["CONTEXTMENU", "contextmenu", "APPS"] {
+            let key = VirtualKey::from_keyname(name).unwrap();
+            assert_eq!(key, VirtualKey::Apps);
+            assert_eq!(key.to_vk_code(), VK_APPS);
+        }
+        // MENU keeps naming the Alt key, like VK_MENU
+        assert_eq!(
+            VirtualKey::from_keyname("MENU").unwrap().to_vk_code(),
+            VK_MENU
+        );
+    }
+
+    #[test]
+    fn short_names() {
+        assert_eq!(VirtualKey::Escape.short_name(), "Esc");
+        assert_eq!(VirtualKey::Q.short_name(), "Q");
+        assert_eq!(VirtualKey::Numpad7.short_name(), "Num 7");
+        // Keys without a short label fall back to the constant name used by Display
+        assert_eq!(VirtualKey::Clear.short_name(), "VK_CLEAR");
+        assert_eq!(VirtualKey::Clear.to_string(), "VK_CLEAR");
+        assert_eq!(VirtualKey::CustomKeyCode(0x07).short_name(), "Unknown");
+    }
+
+    #[test]
+    fn punctuation_chars_map_to_oem_keys() {
+        let cases = [
+            ('-', VK_OEM_MINUS),
+            ('_', VK_OEM_MINUS),
+            (',', VK_OEM_COMMA),
+            ('/', VK_OEM_2),
+            ('?', VK_OEM_2),
+            (';', VK_OEM_1),
+            ('"', VK_OEM_7),
+        ];
+        for (ch, vk_code) in cases {
+            assert_eq!(VirtualKey::from_char(ch).unwrap().to_vk_code(), vk_code);
+        }
+
+        for ch in ['!', '*', '€'] {
+            assert_eq!(
+                VirtualKey::from_char(ch),
+                Err(HotkeyError::InvalidKeyChar(ch))
+            );
+        }
+    }
+}
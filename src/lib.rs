@@ -1,16 +1,35 @@
 #![allow(clippy::doc_lazy_continuation)]
-#[cfg(windows)]
+
+#[macro_use]
+mod log;
+
+#[cfg(all(windows, feature = "thread_safe"))]
+pub mod action;
 pub mod error;
 #[cfg(all(windows, feature = "thread_safe"))]
 pub mod global;
 #[cfg(windows)]
+pub mod input;
 pub mod keys;
+#[cfg(all(windows, feature = "polling"))]
+pub mod polling;
+#[cfg(all(windows, feature = "thread_safe"))]
+pub mod runtime;
 #[cfg(windows)]
 pub mod single_thread;
 #[cfg(all(windows, feature = "thread_safe"))]
 pub mod thread_safe;
+#[cfg(not(windows))]
+mod unsupported;
 
 use core::fmt;
+#[cfg(windows)]
+use std::collections::HashMap;
+use std::sync::Arc;
+#[cfg(windows)]
+use std::sync::{Mutex, PoisonError};
+#[cfg(windows)]
+use std::time::{Duration, Instant};
 
 #[cfg(all(windows, feature = "thread_safe"))]
 pub use thread_safe::HotkeyManager;
@@ -18,30 +37,235 @@ pub use thread_safe::HotkeyManager;
 #[cfg(all(windows, not(feature = "thread_safe")))]
 pub use single_thread::HotkeyManager;
 
+#[cfg(not(windows))]
+pub use unsupported::HotkeyManager;
+
 #[cfg(windows)]
 use windows_sys::Win32::Foundation::HWND;
 #[cfg(windows)]
 use windows_sys::Win32::UI::WindowsAndMessaging::{PostMessageW, WM_NULL};
 
-#[cfg(windows)]
 use crate::error::HotkeyError;
-#[cfg(windows)]
 use crate::keys::*;
 
-#[cfg(windows)]
+/// Identifier of a registered hotkey.
+///
+/// The id can be stored as a `u16` and turned back into a `HotkeyId` later, for example to
+/// unregister a hotkey by a persisted id. Ids are only meaningful within the manager instance that
+/// returned them, and are not stable across restarts unless the hotkeys are registered again in the
+/// same order.
+///
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct HotkeyId(u16);
 
+impl HotkeyId {
+    /// Create a `HotkeyId` from a raw id.
+    ///
+    pub const fn new(id: u16) -> Self {
+        Self(id)
+    }
+
+    /// Get the raw id, the counterpart of `new`. This is the same as `as_u16`.
+    ///
+    pub const fn get(&self) -> u16 {
+        self.as_u16()
+    }
+
+    /// Get the raw id that the hotkey was registered with.
+    ///
+    pub const fn as_u16(&self) -> u16 {
+        self.0
+    }
+}
+
+impl From<u16> for HotkeyId {
+    fn from(id: u16) -> Self {
+        Self(id)
+    }
+}
+
+impl From<HotkeyId> for u16 {
+    fn from(id: HotkeyId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for HotkeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// State of a hotkey that is passed to callbacks registered with `register_with_state`.
+///
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum HotkeyState {
+    /// The hotkey was triggered
+    Pressed,
+    /// The main key of the hotkey was released after it was triggered
+    Released,
+}
+
+/// Predicate that decides if a hotkey callback is executed for the current foreground window
+#[cfg(windows)]
+type ForegroundPredicate = dyn Fn(&ForegroundWindow) -> bool + 'static;
+
 /// HotkeyCallback contains the callback function and a list of extra_keys that need to be pressed
 /// together with the hotkey when executing the callback.
 ///
 #[cfg(windows)]
 struct HotkeyCallback<T> {
+    /// The main key that the hotkey is registered with
+    virtual_key: VirtualKey,
+    /// The modifier code that the hotkey is registered with
+    modifiers: u32,
+    /// Scan code of the main key, if the hotkey tracks the physical key across keyboard layouts
+    scan_code: Option<u32>,
     /// Callback function to execute  when the hotkey & extrakeys match
-    callback: Option<Box<dyn Fn() -> T + 'static>>,
+    callback: Option<Arc<dyn Fn() -> T + 'static>>,
+    /// Callback function to execute when the main key is released after the hotkey was triggered
+    on_release: Option<Arc<dyn Fn() -> T + 'static>>,
     /// List of additional VKeys that are required to be pressed to execute
     /// the callback
     extra_keys: Option<Vec<VirtualKey>>,
+    /// Number of presses required within a time window before executing the callback
+    taps: Option<TapCounter>,
+    /// Predicate that the foreground window must match to execute the callback
+    foreground: Option<Arc<ForegroundPredicate>>,
+    /// Whether the hotkey events are delivered. Disabled hotkeys stay registered with windows
+    enabled: bool,
+    /// Callback functions per application mode, used instead of `callback` if present
+    modal: Option<HashMap<u32, Arc<dyn Fn() -> T + 'static>>>,
+    /// Whether lock keys among the extra keys are skipped when matching
+    ignore_lock_keys: bool,
+}
+
+#[cfg(windows)]
+impl<T> HotkeyCallback<T> {
+    fn new(
+        virtual_key: VirtualKey,
+        modifiers: u32,
+        callback: Option<Arc<dyn Fn() -> T + 'static>>,
+        extra_keys: Option<Vec<VirtualKey>>,
+    ) -> Self {
+        Self {
+            virtual_key,
+            modifiers,
+            scan_code: None,
+            callback,
+            on_release: None,
+            extra_keys,
+            taps: None,
+            foreground: None,
+            enabled: true,
+            modal: None,
+            ignore_lock_keys: false,
+        }
+    }
+
+    /// Check if all of the extra keys are currently pressed
+    ///
+    fn extra_keys_pressed(&self) -> bool {
+        self.extra_keys_match(get_raw_keystate)
+    }
+
+    /// Check the extra keys against the given key state, in the format returned by
+    /// `GetAsyncKeyState`. Only the most significant bit, which is set while the key is held down,
+    /// is considered.
+    ///
+    fn extra_keys_match(&self, key_state: impl Fn(VirtualKey) -> u16) -> bool {
+        self.extra_keys
+            .iter()
+            .flatten()
+            .filter(|vk| !(self.ignore_lock_keys && vk.is_lock_key()))
+            .all(|vk| key_state(*vk) & 0x8000 != 0)
+    }
+
+    /// Check if all of the modifiers of the hotkey are currently pressed
+    ///
+    fn modifiers_pressed(&self) -> bool {
+        let pressed = |modifier: ModifiersKey, keys: &[VirtualKey]| {
+            self.modifiers & modifier.to_mod_code() == 0
+                || keys.iter().any(|vk| get_global_keystate(*vk))
+        };
+        pressed(ModifiersKey::Alt, &[VirtualKey::Menu])
+            && pressed(ModifiersKey::Ctrl, &[VirtualKey::Control])
+            && pressed(ModifiersKey::Shift, &[VirtualKey::Shift])
+            && pressed(ModifiersKey::Win, &[VirtualKey::LWin, VirtualKey::RWin])
+    }
+
+    /// Check if the current foreground window matches the foreground predicate, if there is one
+    ///
+    fn foreground_matches(&self) -> bool {
+        match &self.foreground {
+            Some(predicate) => predicate(&ForegroundWindow::current()),
+            None => true,
+        }
+    }
+}
+
+/// TapCounter keeps track of consecutive presses of a multi-tap hotkey.
+///
+#[cfg(windows)]
+#[derive(Debug)]
+struct TapCounter {
+    /// Number of presses required to execute the callback
+    required: u8,
+    /// Time window in which all of the presses must occur, measured from the first press
+    within: Duration,
+    /// Presses counted in the current window. Behind a mutex, since the managers are `Sync` and
+    /// the presses are counted through a shared reference
+    state: Mutex<TapState>,
+}
+
+/// Mutable state of a `TapCounter`.
+///
+#[cfg(windows)]
+#[derive(Debug, Default)]
+struct TapState {
+    /// Number of presses counted in the current window
+    count: u8,
+    /// Time of the first press in the current window
+    first: Option<Instant>,
+}
+
+#[cfg(windows)]
+impl TapCounter {
+    fn new(required: u8, within: Duration) -> Self {
+        Self {
+            required,
+            within,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Count a press and return true if the required number of presses has been reached. The
+    /// counter is reset when the time window lapses or the tap count is reached.
+    ///
+    fn tap(&self) -> bool {
+        self.tap_at(Instant::now())
+    }
+
+    /// Same as `tap`, but for a press at the given time.
+    ///
+    fn tap_at(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.first {
+            Some(first) if now.duration_since(first) <= self.within => {}
+            _ => {
+                state.first = Some(now);
+                state.count = 0;
+            }
+        }
+
+        state.count += 1;
+        if state.count >= self.required {
+            *state = TapState::default();
+            true
+        } else {
+            false
+        }
+    }
 }
 
 #[cfg(windows)]
@@ -51,6 +275,9 @@ where
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("HotkeyCallback")
+            .field("virtual_key", &self.virtual_key)
+            .field("modifiers", &self.modifiers)
+            .field("scan_code", &self.scan_code)
             .field(
                 "callback",
                 &self.callback.as_ref().map_or_else(
@@ -58,13 +285,41 @@ where
                     |_| "Some(Fn() -> T + 'static)".to_string(),
                 ),
             )
+            .field(
+                "on_release",
+                &self.on_release.as_ref().map_or_else(
+                    || "None".to_string(),
+                    |_| "Some(Fn() -> T + 'static)".to_string(),
+                ),
+            )
             .field("extra_keys", &self.extra_keys)
+            .field("taps", &self.taps)
+            .field(
+                "foreground",
+                &self.foreground.as_ref().map_or_else(
+                    || "None".to_string(),
+                    |_| "Some(Fn(&ForegroundWindow) -> bool)".to_string(),
+                ),
+            )
+            .field("enabled", &self.enabled)
+            .field("ignore_lock_keys", &self.ignore_lock_keys)
+            .field(
+                "modal",
+                &self.modal.as_ref().map(|modal| {
+                    let mut modes: Vec<_> = modal.keys().collect();
+                    modes.sort();
+                    modes
+                }),
+            )
             .finish()
     }
 }
 
-#[cfg(windows)]
 pub trait HotkeyManagerImpl<T> {
+    /// Create a new `HotkeyManager` with the default settings. The `NoRepeat` modifier is added to
+    /// all registrations by default, see `set_no_repeat`. Use `HotkeyManager::builder` to make the
+    /// settings explicit at construction.
+    ///
     fn new() -> Self;
 
     /// Register a new hotkey with additional required extra keys.
@@ -101,6 +356,47 @@ pub trait HotkeyManagerImpl<T> {
         callback: Option<impl Fn() -> T + Send + 'static>,
     ) -> Result<HotkeyId, HotkeyError>;
 
+    /// Same as `register_extrakeys`, but refuse to register a typing key (see
+    /// `VirtualKey::is_typing_key`) without any modifiers. Such a hotkey steals the key from every
+    /// other application, which makes it impossible to type it system-wide. Keys like the function
+    /// or media keys can still be registered without modifiers.
+    ///
+    /// Use `register_extrakeys` to register a bare typing key deliberately.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_strict(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        // The NoRepeat flag doesn't need to be pressed, so it doesn't count as a modifier
+        let modifiers =
+            ModifiersKey::combine(modifiers_key) & !ModifiersKey::NoRepeat.to_mod_code();
+        if modifiers == 0 && virtual_key.is_typing_key() {
+            return Err(HotkeyError::MissingModifiers(virtual_key));
+        }
+
+        self.register_extrakeys(virtual_key, modifiers_key, extra_keys, callback)
+    }
+
+    /// Same as `register_extrakeys` but with a shared callback. The same `Arc` can be used to
+    /// register multiple hotkeys, so that one allocation backs all of the registrations.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    fn register_shared(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: Arc<dyn Fn() -> T + Send + Sync + 'static>,
+    ) -> Result<HotkeyId, HotkeyError>;
+
     /// Same as `register_extrakeys` but without extra keys.
     ///
     /// # Windows API Functions used
@@ -159,6 +455,7 @@ pub trait HotkeyManagerImpl<T> {
 /// simply not do anything.
 ///
 #[cfg(windows)]
+#[derive(Debug)]
 pub struct InterruptHandle(HWND);
 
 #[cfg(windows)]
@@ -172,26 +469,213 @@ impl InterruptHandle {
     /// Interrupt the evet loop of the associated `HotkeyManager`.
     ///
     pub fn interrupt(&self) {
+        // Without a window, PostMessageW would post to the message queue of the calling thread
+        if self.0.is_null() {
+            return;
+        }
         unsafe {
             PostMessageW(self.0, WM_NULL, 0, 0);
         }
     }
 }
 
+/// Stand-in for the `InterruptHandle` on platforms other than windows, where there is no event
+/// loop to interrupt.
+///
+#[cfg(not(windows))]
+#[derive(Debug)]
+pub struct InterruptHandle(());
+
+#[cfg(not(windows))]
+impl InterruptHandle {
+    /// Does nothing, since there is no event loop on this platform.
+    ///
+    pub fn interrupt(&self) {}
+}
+
+/// The window that is in the foreground while a hotkey is triggered, see
+/// `single_thread::HotkeyManager::register_when_foreground`.
+///
+/// The window title and process are only queried when requested, so a predicate that doesn't need
+/// them stays cheap.
+///
+#[cfg(windows)]
+#[derive(Debug, Clone, Copy)]
+pub struct ForegroundWindow(HWND);
+
+#[cfg(windows)]
+impl ForegroundWindow {
+    /// Get the current foreground window.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getforegroundwindow>
+    ///
+    fn current() -> Self {
+        use windows_sys::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+        Self(unsafe { GetForegroundWindow() })
+    }
+
+    /// Get the raw window handle. This is null if no window is in the foreground, for example
+    /// while the focus is switching between windows.
+    ///
+    pub fn hwnd(&self) -> HWND {
+        self.0
+    }
+
+    /// Get the title of the window. Returns an empty string if the window has no title.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowtextlengthw>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowtextw>
+    ///
+    pub fn title(&self) -> String {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{GetWindowTextLengthW, GetWindowTextW};
+
+        let len = unsafe { GetWindowTextLengthW(self.0) };
+        if len <= 0 {
+            return String::new();
+        }
+
+        let mut buffer = vec![0u16; len as usize + 1];
+        let copied = unsafe { GetWindowTextW(self.0, buffer.as_mut_ptr(), buffer.len() as i32) };
+        String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+    }
+
+    /// Get the id of the process that created the window.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getwindowthreadprocessid>
+    ///
+    pub fn process_id(&self) -> u32 {
+        use windows_sys::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+        let mut process_id = 0;
+        unsafe { GetWindowThreadProcessId(self.0, &mut process_id) };
+        process_id
+    }
+}
+
 /// Get the global keystate for a given Virtual Key.
 ///
 /// Return true if the key is pressed, false otherwise.
 ///
+/// `GetAsyncKeyState` doesn't report the toggle state of CAPS LOCK, NUM LOCK or SCROLL LOCK, so
+/// extra keys match the same way whether a lock key is toggled on or not, and a lock key used as
+/// an extra key only counts while it is held down. Use `is_key_toggled` to check the toggle state
+/// instead.
+///
 /// ## Windows API Functions used
 /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
 ///
 #[cfg(windows)]
 pub fn get_global_keystate(vk: VirtualKey) -> bool {
     // Most significant bit represents key state (1 => pressed, 0 => not pressed)
+    get_raw_keystate(vk) & 0x8000 != 0
+}
+
+/// Get the key state for a given Virtual Key as returned by `GetAsyncKeyState`.
+///
+#[cfg(windows)]
+fn get_raw_keystate(vk: VirtualKey) -> u16 {
     use windows_sys::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
-    let key_state = unsafe { GetAsyncKeyState(vk.to_vk_code() as i32) };
-    // Get most significant bit only
-    let key_state = key_state as u32 >> 31;
+    unsafe { GetAsyncKeyState(vk.to_vk_code() as i32) as u16 }
+}
+
+/// Check if the key is currently held down. This is the same check that is used for the extra
+/// keys of a hotkey, and works regardless of which application has the focus.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+///
+#[cfg(windows)]
+pub fn is_key_down(key: VirtualKey) -> bool {
+    get_global_keystate(key)
+}
 
-    key_state == 1
+/// Check if a toggle key like `VirtualKey::Capital` (CAPS LOCK) or `VirtualKey::Numlock` is
+/// currently toggled on. For other keys, the toggle state flips with every key press and doesn't
+/// carry any meaning.
+///
+/// ## Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeystate>
+///
+#[cfg(windows)]
+pub fn is_key_toggled(key: VirtualKey) -> bool {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::GetKeyState;
+    let key_state = unsafe { GetKeyState(key.to_vk_code() as i32) };
+    // Least significant bit represents the toggle state (1 => toggled, 0 => not toggled)
+    key_state & 1 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotkey_id_round_trips_through_u16() {
+        let id = HotkeyId::new(0xBFFF);
+        assert_eq!(id.get(), 0xBFFF);
+        assert_eq!(id.as_u16(), 0xBFFF);
+        assert_eq!(u16::from(id), 0xBFFF);
+        assert_eq!(HotkeyId::from(id.get()), id);
+
+        let raw: u16 = HotkeyId::new(7).into();
+        assert_eq!(HotkeyId::new(raw), HotkeyId::new(7));
+        assert_eq!(HotkeyId::new(7).to_string(), "7");
+    }
+
+    #[cfg(windows)]
+    fn handler_with_extra_keys(extra_keys: &[VirtualKey]) -> HotkeyCallback<()> {
+        HotkeyCallback::new(VirtualKey::A, 0, None, Some(extra_keys.to_vec()))
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn toggled_lock_key_does_not_affect_extra_keys() {
+        let handler = handler_with_extra_keys(&[VirtualKey::B]);
+        // B is held down and CAPS LOCK is toggled on, but not held down
+        let state = |vk: VirtualKey| match vk {
+            VirtualKey::B => 0x8000,
+            VirtualKey::Capital => 0x0001,
+            _ => 0,
+        };
+        assert!(handler.extra_keys_match(state));
+
+        // Only the least significant bit is set for B, which doesn't count as held down
+        assert!(!handler.extra_keys_match(|vk| u16::from(vk == VirtualKey::B)));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn lock_keys_can_be_ignored() {
+        let mut handler = handler_with_extra_keys(&[VirtualKey::B, VirtualKey::Capital]);
+        let state = |vk: VirtualKey| if vk == VirtualKey::B { 0x8000 } else { 0 };
+        assert!(!handler.extra_keys_match(state));
+
+        handler.ignore_lock_keys = true;
+        assert!(handler.extra_keys_match(state));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn tap_counter_counts_taps_within_window() {
+        let counter = TapCounter::new(2, Duration::from_millis(300));
+        let start = Instant::now();
+
+        assert!(!counter.tap_at(start));
+        assert!(counter.tap_at(start + Duration::from_millis(200)));
+        // The counter starts over after reaching the tap count
+        assert!(!counter.tap_at(start + Duration::from_millis(250)));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn tap_counter_resets_after_window_lapses() {
+        let counter = TapCounter::new(2, Duration::from_millis(300));
+        let start = Instant::now();
+
+        assert!(!counter.tap_at(start));
+        assert!(!counter.tap_at(start + Duration::from_millis(400)));
+        assert!(counter.tap_at(start + Duration::from_millis(500)));
+    }
 }
@@ -0,0 +1,32 @@
+//! Logging macros that forward to `tracing` when the `tracing` feature is enabled. Without the
+//! feature, warnings and errors are printed to stderr and debug messages are discarded.
+
+// Not all macros are used on every platform and feature combination
+#![allow(unused_macros)]
+
+#[cfg(feature = "tracing")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { tracing::debug!($($arg)*) };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {{
+        let _ = format_args!($($arg)*);
+    }};
+}
+#[cfg(feature = "tracing")]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { tracing::warn!($($arg)*) };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { eprintln!($($arg)*) };
+}
+#[cfg(feature = "tracing")]
+macro_rules! log_error {
+    ($($arg:tt)*) => { tracing::error!($($arg)*) };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_error {
+    ($($arg:tt)*) => { eprintln!($($arg)*) };
+}
@@ -0,0 +1,232 @@
+//! Polling based detection for key combinations that `RegisterHotKey` can't express.
+//!
+//! `RegisterHotKey` always needs a non-modifier main key, so reacting to a lone modifier tap (like
+//! tapping SHIFT) isn't possible with the regular hotkey managers. The watchers in this module run
+//! a background thread that polls the global keystate instead.
+//!
+//! Polling is less precise than a registered hotkey: very short key presses that happen entirely
+//! between two polls are missed, and the keystate is only observed, so the key press is not
+//! consumed and still reaches the focused application.
+//!
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{sleep, spawn, JoinHandle};
+use std::time::{Duration, Instant};
+
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+
+use crate::error::HotkeyError;
+use crate::get_global_keystate;
+use crate::keys::{ModifiersKey, VirtualKey};
+
+/// Interval in which the keystate is polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Longest time the modifier can be held down for the release to still count as a tap.
+const TAP_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Watch for a tap of a single modifier key, meaning a press and release of the modifier without
+/// any other key being pressed in between. The callback is executed on a background thread after
+/// the modifier is released.
+///
+/// Generic modifiers like `ModifiersKey::Shift` are tapped by either the left or right key, while
+/// sided modifiers like `ModifiersKey::LShift` only count their own key. For a sided modifier,
+/// pressing the opposite key cancels the tap. Mouse buttons count as other keys as well, so a
+/// SHIFT + click is not a tap. Holding the modifier for longer than 500ms isn't a tap either.
+///
+/// The watcher runs until the returned `ModifierTapWatcher` is dropped or stopped.
+///
+/// # Windows API Functions used
+/// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+///
+pub fn watch_modifier_tap(
+    modifier: ModifiersKey,
+    callback: impl Fn() + Send + 'static,
+) -> Result<ModifierTapWatcher, HotkeyError> {
+    let (tap_keys, ignored_keys) = tap_keys(modifier)?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let handle = spawn({
+        let running = running.clone();
+        move || {
+            let mut detector = TapDetector::new();
+            while running.load(Ordering::Relaxed) {
+                let modifier_down = tap_keys
+                    .iter()
+                    .any(|vk| get_global_keystate(VirtualKey::CustomKeyCode(*vk)));
+                let other_down = (1..=0xFE)
+                    .filter(|vk| !ignored_keys.contains(vk))
+                    .any(|vk| get_global_keystate(VirtualKey::CustomKeyCode(vk)));
+
+                if detector.update(Instant::now(), modifier_down, other_down) {
+                    callback();
+                }
+                sleep(POLL_INTERVAL);
+            }
+        }
+    });
+
+    Ok(ModifierTapWatcher {
+        running,
+        handle: Some(handle),
+    })
+}
+
+/// Handle to a running `watch_modifier_tap` watcher. Dropping the handle stops the watcher.
+///
+#[derive(Debug)]
+pub struct ModifierTapWatcher {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ModifierTapWatcher {
+    /// Stop the watcher and wait for the background thread to finish.
+    ///
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl Drop for ModifierTapWatcher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Get the virtual key codes that count as pressing the modifier, and the codes that are not
+/// considered as "other keys" while the modifier is held down.
+///
+fn tap_keys(modifier: ModifiersKey) -> Result<(Vec<u16>, Vec<u16>), HotkeyError> {
+    Ok(match modifier {
+        ModifiersKey::Alt => (vec![VK_MENU], vec![VK_MENU, VK_LMENU, VK_RMENU]),
+        ModifiersKey::LAlt => (vec![VK_LMENU], vec![VK_MENU, VK_LMENU]),
+        ModifiersKey::RAlt => (vec![VK_RMENU], vec![VK_MENU, VK_RMENU]),
+        ModifiersKey::Ctrl => (vec![VK_CONTROL], vec![VK_CONTROL, VK_LCONTROL, VK_RCONTROL]),
+        ModifiersKey::LCtrl => (vec![VK_LCONTROL], vec![VK_CONTROL, VK_LCONTROL]),
+        ModifiersKey::RCtrl => (vec![VK_RCONTROL], vec![VK_CONTROL, VK_RCONTROL]),
+        ModifiersKey::Shift => (vec![VK_SHIFT], vec![VK_SHIFT, VK_LSHIFT, VK_RSHIFT]),
+        ModifiersKey::LShift => (vec![VK_LSHIFT], vec![VK_SHIFT, VK_LSHIFT]),
+        ModifiersKey::RShift => (vec![VK_RSHIFT], vec![VK_SHIFT, VK_RSHIFT]),
+        ModifiersKey::Win => (vec![VK_LWIN, VK_RWIN], vec![VK_LWIN, VK_RWIN]),
+        ModifiersKey::LWin => (vec![VK_LWIN], vec![VK_LWIN]),
+        ModifiersKey::RWin => (vec![VK_RWIN], vec![VK_RWIN]),
+        ModifiersKey::NoRepeat | ModifiersKey::Non => {
+            return Err(HotkeyError::InvalidKey(modifier.to_string()))
+        }
+    })
+}
+
+/// State of the tap detection.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TapState {
+    /// The modifier is not pressed
+    Idle,
+    /// The modifier was pressed at the given time and no other key was pressed so far
+    Armed(Instant),
+    /// Another key was pressed together with the modifier, wait until the modifier is released
+    Cancelled,
+}
+
+/// State machine that detects a modifier tap from consecutive keystate samples.
+///
+#[derive(Debug)]
+struct TapDetector {
+    state: TapState,
+}
+
+impl TapDetector {
+    /// Start as cancelled, so that a modifier that is already held down when the detector is
+    /// created doesn't count as a tap.
+    ///
+    fn new() -> Self {
+        Self {
+            state: TapState::Cancelled,
+        }
+    }
+
+    /// Feed a keystate sample taken at `now` into the detector. Return true if the sample
+    /// completes a tap.
+    ///
+    fn update(&mut self, now: Instant, modifier_down: bool, other_down: bool) -> bool {
+        let (state, tapped) = match self.state {
+            TapState::Idle if modifier_down && other_down => (TapState::Cancelled, false),
+            TapState::Idle if modifier_down => (TapState::Armed(now), false),
+            TapState::Armed(_) if other_down => (TapState::Cancelled, false),
+            TapState::Armed(pressed) if now.duration_since(pressed) > TAP_TIMEOUT => {
+                if modifier_down {
+                    (TapState::Cancelled, false)
+                } else {
+                    (TapState::Idle, false)
+                }
+            }
+            TapState::Armed(_) if !modifier_down => (TapState::Idle, true),
+            TapState::Cancelled if !modifier_down => (TapState::Idle, false),
+            state => (state, false),
+        };
+        self.state = state;
+        tapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed the samples into a detector that starts idle, one every `POLL_INTERVAL`, and return the
+    /// samples that completed a tap.
+    fn taps(samples: &[(bool, bool)]) -> Vec<usize> {
+        let mut detector = TapDetector {
+            state: TapState::Idle,
+        };
+        let start = Instant::now();
+        samples
+            .iter()
+            .enumerate()
+            .filter(|(i, (modifier_down, other_down))| {
+                let now = start + POLL_INTERVAL * *i as u32;
+                detector.update(now, *modifier_down, *other_down)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn press_and_release_is_a_tap() {
+        assert_eq!(
+            taps(&[(false, false), (true, false), (true, false), (false, false)]),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn other_key_cancels_tap() {
+        assert_eq!(
+            taps(&[(true, false), (true, true), (true, false), (false, false)]),
+            Vec::<usize>::new()
+        );
+        // The modifier pressed while another key is already down isn't a tap either
+        assert_eq!(taps(&[(true, true), (false, false)]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn holding_too_long_is_no_tap() {
+        let held = (TAP_TIMEOUT.as_millis() / POLL_INTERVAL.as_millis()) as usize + 2;
+        let mut samples = vec![(true, false); held];
+        samples.push((false, false));
+        assert_eq!(taps(&samples), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn modifier_held_at_start_is_no_tap() {
+        let mut detector = TapDetector::new();
+        let now = Instant::now();
+        assert!(!detector.update(now, true, false));
+        assert!(!detector.update(now + POLL_INTERVAL, false, false));
+    }
+}
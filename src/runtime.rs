@@ -0,0 +1,260 @@
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread::spawn;
+use std::thread::JoinHandle;
+
+use crate::error::HotkeyError;
+use crate::keys::ModifiersKey;
+use crate::keys::VirtualKey;
+use crate::single_thread;
+use crate::HotkeyId;
+use crate::HotkeyManagerImpl;
+use crate::InterruptHandle;
+
+type RuntimeCallback = Box<dyn Fn() + Send + 'static>;
+
+enum RuntimeMessage {
+    Register(
+        Sender<Result<HotkeyId, HotkeyError>>,
+        VirtualKey,
+        Option<Vec<ModifiersKey>>,
+        Option<Vec<VirtualKey>>,
+        RuntimeCallback,
+    ),
+    Unregister(Sender<Result<(), HotkeyError>>, HotkeyId),
+    Exit,
+}
+
+/// A single hidden message window and background thread that can be shared by many lightweight
+/// `RuntimeHandle`s. Creating and dropping handles is cheap, since it doesn't create or destroy
+/// any windows or threads. This is useful for applications that register and unregister hotkeys
+/// frequently from different places.
+///
+/// The background thread runs the event loop for as long as the runtime exists, so the hotkeys
+/// are active right after registering them.
+///
+/// ## Thread affinity
+/// Windows delivers hotkey messages to the thread that owns the window the hotkey was registered
+/// with. All hotkeys of the runtime are registered on the runtime thread, so all callbacks are
+/// executed on that thread too, regardless of the thread the handle was used on. A long running
+/// callback blocks all other hotkeys of the runtime, as well as registrations through its handles.
+///
+#[derive(Debug)]
+pub struct SharedHotkeyRuntime {
+    sender: Sender<RuntimeMessage>,
+    interrupt: Arc<InterruptHandle>,
+    backend_handle: Option<JoinHandle<()>>,
+}
+
+/// Handle for registering hotkeys with a `SharedHotkeyRuntime`, created by
+/// `SharedHotkeyRuntime::handle`. It is not called `HotkeyHandle`, since that is the name of the
+/// handle to a thread safe `HotkeyManager`, which doesn't own its hotkeys.
+///
+/// All hotkeys registered through a handle are unregistered when the handle is dropped, while the
+/// runtime keeps running. If the runtime was dropped already, all calls return
+/// `HotkeyError::BackendGone`.
+///
+#[derive(Debug)]
+pub struct RuntimeHandle {
+    sender: Sender<RuntimeMessage>,
+    interrupt: Arc<InterruptHandle>,
+    ids: Vec<HotkeyId>,
+}
+
+impl SharedHotkeyRuntime {
+    /// Create the runtime. This launches the background thread, which creates the hidden window
+    /// and runs the event loop.
+    ///
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        let (interrupt_sender, interrupt_receiver) = channel();
+
+        let backend_handle = spawn(move || {
+            let hkm = single_thread::HotkeyManager::new();
+            let _ = interrupt_sender.send(hkm.interrupt_handle());
+            runtime_loop(hkm, receiver);
+        });
+
+        let interrupt = interrupt_receiver
+            .recv()
+            .unwrap_or(InterruptHandle(std::ptr::null_mut()));
+
+        Self {
+            sender,
+            interrupt: Arc::new(interrupt),
+            backend_handle: Some(backend_handle),
+        }
+    }
+
+    /// Create a new handle for registering hotkeys with this runtime.
+    ///
+    pub fn handle(&self) -> RuntimeHandle {
+        RuntimeHandle {
+            sender: self.sender.clone(),
+            interrupt: self.interrupt.clone(),
+            ids: Vec::new(),
+        }
+    }
+}
+
+impl Default for SharedHotkeyRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SharedHotkeyRuntime {
+    fn drop(&mut self) {
+        if self.sender.send(RuntimeMessage::Exit).is_ok() {
+            self.interrupt.interrupt();
+        }
+        if let Some(backend_handle) = self.backend_handle.take() {
+            let _ = backend_handle.join();
+        }
+    }
+}
+
+impl RuntimeHandle {
+    /// Register a new hotkey with the runtime. The callback is executed on the runtime thread.
+    /// See `HotkeyManagerImpl::register_extrakeys` for the arguments.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: impl Fn() + Send + 'static,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let id = self.send_message(|channel| {
+            RuntimeMessage::Register(
+                channel,
+                virtual_key,
+                modifiers_key.map(|keys| keys.to_vec()),
+                extra_keys.map(|keys| keys.to_vec()),
+                Box::new(callback),
+            )
+        })??;
+        self.ids.push(id);
+        Ok(id)
+    }
+
+    /// Unregister a hotkey that was registered through this handle.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    pub fn unregister(&mut self, id: HotkeyId) -> Result<(), HotkeyError> {
+        let Some(index) = self.ids.iter().position(|registered| *registered == id) else {
+            return Err(HotkeyError::UnknownHotkeyId(id));
+        };
+
+        self.send_message(|channel| RuntimeMessage::Unregister(channel, id))??;
+        self.ids.remove(index);
+        Ok(())
+    }
+
+    /// Get the ids of the hotkeys registered through this handle.
+    ///
+    pub fn ids(&self) -> &[HotkeyId] {
+        &self.ids
+    }
+
+    /// Send a message to the runtime thread, wake it up and wait for the response.
+    ///
+    fn send_message<R>(
+        &self,
+        message: impl FnOnce(Sender<R>) -> RuntimeMessage,
+    ) -> Result<R, HotkeyError> {
+        let (sender, receiver) = channel();
+        self.sender
+            .send(message(sender))
+            .map_err(|_| HotkeyError::BackendGone)?;
+        self.interrupt.interrupt();
+        receiver.recv().map_err(|_| HotkeyError::BackendGone)
+    }
+}
+
+impl Drop for RuntimeHandle {
+    fn drop(&mut self) {
+        for id in std::mem::take(&mut self.ids) {
+            let _ = self.send_message(|channel| RuntimeMessage::Unregister(channel, id));
+        }
+    }
+}
+
+/// Run the event loop of the runtime thread. The event loop is interrupted whenever a message is
+/// sent to the runtime, so the pending messages are processed before listening again.
+///
+/// If the message queue can't be read, the runtime thread exits instead of retrying. The receiver
+/// is dropped with it, so the handles return `HotkeyError::BackendGone` from then on.
+///
+fn runtime_loop(mut hkm: single_thread::HotkeyManager<()>, receiver: Receiver<RuntimeMessage>) {
+    loop {
+        if let Err(err) = hkm.try_event_loop() {
+            log_error!("hotkey runtime stopped: {}", err);
+            return;
+        }
+
+        while let Ok(msg) = receiver.try_recv() {
+            match msg {
+                RuntimeMessage::Register(channel, virtual_key, modifiers_key, extra_keys, cb) => {
+                    let return_value = hkm.register_extrakeys(
+                        virtual_key,
+                        modifiers_key.as_deref(),
+                        extra_keys.as_deref(),
+                        Some(cb),
+                    );
+                    let _ = channel.send(return_value);
+                }
+                RuntimeMessage::Unregister(channel, id) => {
+                    let _ = channel.send(hkm.unregister(id));
+                }
+                RuntimeMessage::Exit => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODIFIERS: &[ModifiersKey] =
+        &[ModifiersKey::Ctrl, ModifiersKey::Alt, ModifiersKey::Shift];
+
+    #[test]
+    fn two_handles_share_one_runtime() {
+        let runtime = SharedHotkeyRuntime::new();
+        let mut first = runtime.handle();
+        let mut second = runtime.handle();
+
+        let first_id = first
+            .register(VirtualKey::F23, Some(MODIFIERS), None, || {})
+            .unwrap();
+        let second_id = second
+            .register(VirtualKey::F24, Some(MODIFIERS), None, || {})
+            .unwrap();
+        assert_ne!(first_id, second_id);
+        assert_eq!(first.ids(), [first_id]);
+        assert_eq!(second.ids(), [second_id]);
+
+        // A handle can only unregister its own hotkeys
+        assert_eq!(
+            second.unregister(first_id),
+            Err(HotkeyError::UnknownHotkeyId(first_id))
+        );
+
+        // Dropping a handle unregisters its hotkeys, while the other handle keeps working
+        drop(first);
+        let id = second
+            .register(VirtualKey::F23, Some(MODIFIERS), None, || {})
+            .unwrap();
+        assert_eq!(second.ids(), [second_id, id]);
+        assert_eq!(second.unregister(second_id), Ok(()));
+    }
+}
@@ -1,19 +1,41 @@
 #[cfg(not(target_os = "windows"))]
 compile_error!("Only supported on windows");
 
+use std::any::Any;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::marker::PhantomData;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::sync::Weak;
+use std::thread::sleep;
+use std::time::Duration;
 
 use windows_sys::core::PCSTR;
+use windows_sys::Win32::Foundation::GetLastError;
+use windows_sys::Win32::Foundation::ERROR_HOTKEY_ALREADY_REGISTERED;
 use windows_sys::Win32::Foundation::HWND;
 use windows_sys::Win32::System::LibraryLoader::GetModuleHandleA;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayout;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::MapVirtualKeyExW;
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::RegisterHotKey;
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::UnregisterHotKey;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::HKL;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::MAPVK_VK_TO_VSC;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::MAPVK_VSC_TO_VK;
 use windows_sys::Win32::UI::WindowsAndMessaging::CreateWindowExA;
 use windows_sys::Win32::UI::WindowsAndMessaging::DestroyWindow;
 use windows_sys::Win32::UI::WindowsAndMessaging::GetMessageW;
+use windows_sys::Win32::UI::WindowsAndMessaging::PeekMessageW;
 use windows_sys::Win32::UI::WindowsAndMessaging::HWND_MESSAGE;
 use windows_sys::Win32::UI::WindowsAndMessaging::MSG;
+use windows_sys::Win32::UI::WindowsAndMessaging::PM_REMOVE;
 use windows_sys::Win32::UI::WindowsAndMessaging::WM_HOTKEY;
 use windows_sys::Win32::UI::WindowsAndMessaging::WM_NULL;
 use windows_sys::Win32::UI::WindowsAndMessaging::WS_DISABLED;
@@ -21,11 +43,18 @@ use windows_sys::Win32::UI::WindowsAndMessaging::WS_EX_NOACTIVATE;
 
 use crate::error::HotkeyError;
 use crate::get_global_keystate;
+use crate::get_raw_keystate;
 use crate::keys::*;
+use crate::ForegroundWindow;
 use crate::HotkeyCallback;
 use crate::HotkeyId;
 use crate::HotkeyManagerImpl;
+use crate::HotkeyState;
 use crate::InterruptHandle;
+use crate::TapCounter;
+
+/// Default interval in which the key state is polled while waiting for a hotkey to be released.
+const DEFAULT_RELEASE_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 #[derive(Debug, Clone)]
 struct DropHWND(HWND);
@@ -33,99 +62,1404 @@ struct DropHWND(HWND);
 unsafe impl Send for DropHWND {}
 unsafe impl Sync for DropHWND {}
 
-impl Drop for DropHWND {
-    fn drop(&mut self) {
-        if !self.0.is_null() {
-            let _ = unsafe { DestroyWindow(self.0) };
-        }
+/// Destroys the window when dropped. `DestroyWindow` only works on the thread that created the
+/// window, so dropping it on another thread leaks the window. This is logged, since there is no
+/// way to report an error from `drop`.
+impl Drop for DropHWND {
+    fn drop(&mut self) {
+        if !self.0.is_null() && unsafe { DestroyWindow(self.0) } == 0 {
+            log_warn!(
+                "failed to destroy the hidden hotkey window (os error {}), it might have been \
+                 dropped on a different thread than the one that created it",
+                unsafe { GetLastError() }
+            );
+        }
+    }
+}
+
+/// Hidden window that receives the hotkey messages. The hotkey ids are allocated from the window,
+/// so they stay unique when the window is shared by multiple `HotkeyManager`s.
+#[derive(Debug)]
+struct HotkeyWindow {
+    hwnd: DropHWND,
+    ids: Mutex<IdAllocator>,
+}
+
+impl HotkeyWindow {
+    fn new() -> Self {
+        Self::try_new().unwrap_or_else(|err| {
+            log_error!("{}, hotkeys can't be registered", err);
+            Self::without_window()
+        })
+    }
+
+    /// Placeholder used when the window can't be created, registering hotkeys with it fails.
+    ///
+    fn without_window() -> Self {
+        Self {
+            hwnd: DropHWND(std::ptr::null_mut()),
+            ids: Mutex::default(),
+        }
+    }
+
+    fn try_new() -> Result<Self, HotkeyError> {
+        Ok(Self {
+            hwnd: create_hidden_window()?,
+            ids: Mutex::default(),
+        })
+    }
+
+    fn next_id(&self) -> Result<HotkeyId, HotkeyError> {
+        let mut ids = self.ids.lock().unwrap_or_else(PoisonError::into_inner);
+        ids.next().map(HotkeyId).ok_or(HotkeyError::IdsExhausted)
+    }
+
+    /// Make the id of an unregistered hotkey available again.
+    ///
+    fn release_id(&self, id: HotkeyId) {
+        let mut ids = self.ids.lock().unwrap_or_else(PoisonError::into_inner);
+        ids.release(id.0);
+    }
+}
+
+/// Allocator for the hotkey ids of a window. Ids that were never used are handed out first, and
+/// the ids of unregistered hotkeys are only reused once those run out. This keeps a stale id from
+/// referring to a new hotkey for as long as possible.
+///
+/// Applications may only use the ids up to `MAX_HOTKEY_ID`, the ids above are reserved for shared
+/// DLLs.
+#[derive(Debug, Default)]
+struct IdAllocator {
+    /// Next id that was never handed out
+    next: u32,
+    /// Ids of unregistered hotkeys, in the order they were released
+    free: VecDeque<u16>,
+}
+
+/// Largest hotkey id that `RegisterHotKey` accepts for applications.
+const MAX_HOTKEY_ID: u16 = 0xBFFF;
+
+impl IdAllocator {
+    fn next(&mut self) -> Option<u16> {
+        if self.next <= MAX_HOTKEY_ID as u32 {
+            let id = self.next as u16;
+            self.next += 1;
+            Some(id)
+        } else {
+            self.free.pop_front()
+        }
+    }
+
+    fn release(&mut self, id: u16) {
+        self.free.push_back(id);
+    }
+}
+
+thread_local! {
+    static SHARED_WINDOW: RefCell<Weak<HotkeyWindow>> = const { RefCell::new(Weak::new()) };
+}
+
+#[derive(Debug)]
+pub struct HotkeyManager<T> {
+    window: Arc<HotkeyWindow>,
+    handlers: HashMap<HotkeyId, HotkeyCallback<T>>,
+    // Handlers that share the windows registration of another handler with the same key
+    // combination, mapped to the id the combination is registered with
+    attached: HashMap<HotkeyId, HotkeyId>,
+    no_repeat: bool,
+    track_layout: bool,
+    ignore_lock_keys: bool,
+    layout: HKL,
+    // Behind a mutex, since the manager is `Sync` and the events can be read through `&self`
+    pending_releases: Mutex<Vec<HotkeyId>>,
+    release_poll_interval: Duration,
+    context: Option<Arc<dyn Any + Send + Sync>>,
+    // Atomic, since the mode can be switched from another thread while the event loop is running
+    mode: AtomicU32,
+    results: Option<Sender<T>>,
+    _unimpl_send_sync: PhantomData<*const u8>,
+}
+
+unsafe impl<T> Send for HotkeyManager<T> {}
+unsafe impl<T> Sync for HotkeyManager<T> {}
+
+/// Same as `HotkeyManager::new`. If the hidden window can't be created, the manager can't receive
+/// any hotkeys, use `HotkeyManager::try_new` to detect this.
+impl<T> Default for HotkeyManager<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for a `HotkeyManager`, created by `HotkeyManager::builder`.
+///
+#[derive(Debug)]
+pub struct HotkeyManagerBuilder<T> {
+    no_repeat: bool,
+    release_poll_interval: Duration,
+    results: Option<Sender<T>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> HotkeyManagerBuilder<T> {
+    /// Set whether the `ModKey::NoRepeat` modifier is automatically applied to all registrations.
+    /// Defaults to `true`, see `HotkeyManager::set_no_repeat`.
+    ///
+    pub fn no_repeat(mut self, no_repeat: bool) -> Self {
+        self.no_repeat = no_repeat;
+        self
+    }
+
+    /// Set the interval in which the key state is polled while waiting for a hotkey to be
+    /// released. Defaults to 10ms, see `HotkeyManager::set_release_poll_interval`.
+    ///
+    pub fn release_poll_interval(mut self, interval: Duration) -> Self {
+        self.release_poll_interval = interval;
+        self
+    }
+
+    /// Set a channel that receives the return values of the callbacks executed by the event
+    /// loop, see `HotkeyManager::set_result_sender`.
+    ///
+    pub fn result_sender(mut self, sender: Sender<T>) -> Self {
+        self.results = Some(sender);
+        self
+    }
+
+    /// Create the `HotkeyManager` with the configured settings.
+    ///
+    pub fn build(self) -> HotkeyManager<T> {
+        let mut hkm = HotkeyManager::new();
+        hkm.set_no_repeat(self.no_repeat);
+        hkm.set_release_poll_interval(self.release_poll_interval);
+        hkm.set_result_sender(self.results);
+        hkm
+    }
+}
+
+impl<T> HotkeyManager<T> {
+    /// Create a builder to configure a new `HotkeyManager` explicitly.
+    ///
+    pub fn builder() -> HotkeyManagerBuilder<T> {
+        HotkeyManagerBuilder {
+            no_repeat: true,
+            release_poll_interval: DEFAULT_RELEASE_POLL_INTERVAL,
+            results: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Same as `new`, but return `HotkeyError::WindowCreationFailed` if the hidden window that
+    /// receives the hotkey messages can't be created. `new` creates a manager without a window in
+    /// that case, which fails to register any hotkeys.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createwindowexa>
+    ///
+    pub fn try_new() -> Result<Self, HotkeyError> {
+        Ok(Self::with_window(Arc::new(HotkeyWindow::try_new()?)))
+    }
+
+    /// Create a new `HotkeyManager` that shares its hidden window with the other managers created
+    /// by `new_shared` or `try_new_shared` on the current thread, instead of creating a window for
+    /// each manager. The window is destroyed once the last manager using it is dropped.
+    ///
+    /// This saves window handles when a lot of managers are used, but all managers sharing the
+    /// window read from the same message queue. A hotkey event is received by whichever manager is
+    /// running its event loop, and is discarded if the hotkey belongs to another manager. So only
+    /// one of the managers sharing the window should run the event loop at a time, and it only
+    /// executes its own callbacks. Use `new` for managers that need to listen independently.
+    ///
+    /// If the window can't be created, the manager fails to register any hotkeys, like with `new`.
+    /// The failed window is not shared, so the next call tries to create it again.
+    ///
+    pub fn new_shared() -> Self {
+        Self::try_new_shared().unwrap_or_else(|err| {
+            log_error!("{}, hotkeys can't be registered", err);
+            Self::with_window(Arc::new(HotkeyWindow::without_window()))
+        })
+    }
+
+    /// Same as `new_shared`, but return `HotkeyError::WindowCreationFailed` if there is no shared
+    /// window on the current thread yet and it can't be created.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-createwindowexa>
+    ///
+    pub fn try_new_shared() -> Result<Self, HotkeyError> {
+        let window = SHARED_WINDOW.with(|shared| {
+            let mut shared = shared.borrow_mut();
+            if let Some(window) = shared.upgrade() {
+                return Ok(window);
+            }
+            let window = Arc::new(HotkeyWindow::try_new()?);
+            *shared = Arc::downgrade(&window);
+            Ok(window)
+        })?;
+
+        Ok(Self::with_window(window))
+    }
+
+    fn with_window(window: Arc<HotkeyWindow>) -> Self {
+        HotkeyManager {
+            window,
+            handlers: HashMap::new(),
+            attached: HashMap::new(),
+            no_repeat: true,
+            track_layout: false,
+            ignore_lock_keys: false,
+            layout: unsafe { GetKeyboardLayout(0) },
+            pending_releases: Mutex::default(),
+            release_poll_interval: DEFAULT_RELEASE_POLL_INTERVAL,
+            context: None,
+            mode: AtomicU32::new(0),
+            results: None,
+            _unimpl_send_sync: PhantomData,
+        }
+    }
+
+    /// Enable or disable the automatically applied `ModKey::NoRepeat` modifier. By default, this
+    /// option is set to `true` which causes all hotkey registration calls to add the `NoRepeat`
+    /// modifier, thereby disabling automatic retriggers of hotkeys when holding down the keys.
+    ///
+    /// When this option is disabled, the `ModKey::NoRepeat` can still be manually added while
+    /// registering hotkeys.
+    ///
+    /// Note: Setting this flag doesn't change previously registered hotkeys. It only applies to
+    /// registrations performed after calling this function.
+    pub fn set_no_repeat(&mut self, no_repeat: bool) {
+        self.no_repeat = no_repeat;
+    }
+
+    /// Set the interval in which the key state is polled while waiting for a hotkey with a release
+    /// callback to be released. By default, the interval is 10ms.
+    ///
+    /// A shorter interval detects the release with less latency, at the cost of more CPU time
+    /// while a release is pending. No polling happens while no release is pending. The interval is
+    /// read on every poll, so it also applies to releases that are already pending.
+    pub fn set_release_poll_interval(&mut self, interval: Duration) {
+        self.release_poll_interval = interval;
+    }
+
+    /// Set a channel that receives the return values of the callbacks executed by `event_loop`, so
+    /// they can be processed by a different thread than the one running the event loop. Without a
+    /// channel, which is the default, the return values are dropped. `handle_hotkey` still returns
+    /// the value to its caller instead of sending it.
+    ///
+    /// If the receiver is dropped, the return values are dropped as well and the event loop keeps
+    /// running.
+    pub fn set_result_sender(&mut self, sender: Option<Sender<T>>) {
+        self.results = sender;
+    }
+
+    /// Enable or disable tracking of the physical key for layout dependent hotkeys. By default, this
+    /// option is set to `false`.
+    ///
+    /// The virtual keycode of the OEM keys (like `VirtualKey::Semicolon`) depends on the keyboard
+    /// layout, so after switching layouts a hotkey might be bound to a different physical key.
+    /// When this option is enabled, the scan code of the OEM keys is stored during registration,
+    /// so `sync_keyboard_layout` can re-register these hotkeys for the same physical key. Layout
+    /// changes are not detected automatically, `sync_keyboard_layout` needs to be called after the
+    /// layout changed.
+    ///
+    /// Note: Setting this flag doesn't change previously registered hotkeys. It only applies to
+    /// registrations performed after calling this function.
+    pub fn set_track_layout(&mut self, track_layout: bool) {
+        self.track_layout = track_layout;
+    }
+
+    /// Enable or disable skipping the lock keys CAPS LOCK, NUM LOCK and SCROLL LOCK when checking
+    /// the extra keys of a hotkey. By default, this option is set to `false`, so a lock key among
+    /// the extra keys needs to be held down like any other extra key.
+    ///
+    /// The toggle state of the lock keys is never part of the check, regardless of this option,
+    /// see `get_global_keystate`.
+    ///
+    /// Note: Setting this flag doesn't change previously registered hotkeys. It only applies to
+    /// registrations performed after calling this function.
+    pub fn set_ignore_lock_keys(&mut self, ignore_lock_keys: bool) {
+        self.ignore_lock_keys = ignore_lock_keys;
+    }
+
+    /// Re-register the layout dependent hotkeys if the keyboard layout of the current thread has
+    /// changed since the last call, so they stay bound to the same physical key. Only hotkeys
+    /// registered while `set_track_layout` was enabled are affected.
+    ///
+    /// The hidden message-only window of the `HotkeyManager` doesn't receive `WM_INPUTLANGCHANGE`,
+    /// so this needs to be called by the application, for example when its own windows receive
+    /// `WM_INPUTLANGCHANGE`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getkeyboardlayout>
+    /// - <https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-mapvirtualkeyexw>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    pub fn sync_keyboard_layout(&mut self) -> Result<(), HotkeyError> {
+        self.sync_to_layout(unsafe { GetKeyboardLayout(0) })
+    }
+
+    /// Re-register the layout dependent hotkeys for the given keyboard layout, see
+    /// `sync_keyboard_layout`.
+    ///
+    fn sync_to_layout(&mut self, layout: HKL) -> Result<(), HotkeyError> {
+        if layout == self.layout {
+            return Ok(());
+        }
+        self.layout = layout;
+
+        for (id, handler) in self.handlers.iter_mut() {
+            // Attached handlers follow the handler that holds the registration
+            if self.attached.contains_key(id) {
+                continue;
+            }
+            let Some(scan_code) = handler.scan_code else {
+                continue;
+            };
+
+            let vk_code = unsafe { MapVirtualKeyExW(scan_code, MAPVK_VSC_TO_VK, layout) } as u16;
+            if vk_code == 0 || vk_code == handler.virtual_key.to_vk_code() {
+                continue;
+            }
+
+            if unsafe { UnregisterHotKey(self.window.hwnd.0, id.0 as i32) } == 0 {
+                return Err(HotkeyError::UnregistrationFailed);
+            }
+
+            let reg_ok = unsafe {
+                RegisterHotKey(
+                    self.window.hwnd.0,
+                    id.0 as i32,
+                    handler.modifiers,
+                    vk_code as u32,
+                )
+            };
+            if reg_ok == 0 {
+                return Err(HotkeyError::RegistrationFailed);
+            }
+            handler.virtual_key = VirtualKey::CustomKeyCode(vk_code);
+        }
+        self.sync_attached_keys();
+
+        Ok(())
+    }
+
+    /// Update the main key of the attached handlers to the key of the handler that holds their
+    /// registration, after it was changed by `sync_keyboard_layout`.
+    ///
+    fn sync_attached_keys(&mut self) {
+        for (id, registration) in &self.attached {
+            let Some(virtual_key) = self.handlers.get(registration).map(|h| h.virtual_key) else {
+                continue;
+            };
+            if let Some(handler) = self.handlers.get_mut(id) {
+                handler.virtual_key = virtual_key;
+            }
+        }
+    }
+
+    /// Temporarily unregister all hotkeys with windows while running `f`, and register them again
+    /// with the same ids afterwards. The callbacks are kept as is. This can be used to capture a new
+    /// key combination without triggering the existing hotkeys, for example while the user is
+    /// assigning a new hotkey in a settings UI.
+    ///
+    /// The result of `f` is wrapped in a `Result`, since pausing and resuming the hotkeys involves
+    /// calls to windows that can fail. If a hotkey can't be registered again after `f` returns, for
+    /// example because another application claimed the combination in the meantime, it is removed
+    /// from the manager and `HotkeyError::RegistrationFailed` is returned. If `f` panics, the
+    /// hotkeys are registered again before the panic continues.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn with_hotkeys_paused<R>(&mut self, f: impl FnOnce() -> R) -> Result<R, HotkeyError> {
+        self.pause()?;
+        let guard = ResumeOnUnwind(self);
+        let result = f();
+        guard.0.resume()?;
+        Ok(result)
+    }
+
+    /// Unregister all hotkeys with windows, but keep their handlers so they can be registered again
+    /// by `resume`.
+    ///
+    pub(crate) fn pause(&mut self) -> Result<(), HotkeyError> {
+        let ids = self.registered_with_windows();
+        for (i, id) in ids.iter().enumerate() {
+            if unsafe { UnregisterHotKey(self.window.hwnd.0, id.0 as i32) } == 0 {
+                // Restore the hotkeys that were already unregistered
+                for id in &ids[..i] {
+                    self.register_again(*id);
+                }
+                return Err(HotkeyError::UnregistrationFailed);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Register all hotkeys with windows again after `pause`. The hotkeys that can't be registered
+    /// again are removed.
+    ///
+    pub(crate) fn resume(&mut self) -> Result<(), HotkeyError> {
+        let ids = self.registered_with_windows();
+        let mut result = Ok(());
+        for id in ids {
+            if !self.register_again(id) {
+                self.remove_registration(id);
+                result = Err(HotkeyError::RegistrationFailed);
+            }
+        }
+
+        result
+    }
+
+    /// Get the ids of the handlers that hold a windows registration, meaning all handlers that are
+    /// not attached to another one.
+    ///
+    fn registered_with_windows(&self) -> Vec<HotkeyId> {
+        self.handlers
+            .keys()
+            .filter(|id| !self.attached.contains_key(id))
+            .copied()
+            .collect()
+    }
+
+    /// Get the ids of the handlers attached to the registration of the given handler, sorted by id.
+    ///
+    fn attached_to(&self, registration: HotkeyId) -> Vec<HotkeyId> {
+        let mut ids: Vec<_> = self
+            .attached
+            .iter()
+            .filter(|(_, r)| **r == registration)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort_by_key(|id| id.0);
+        ids
+    }
+
+    /// Remove the handler of a registration that is not registered with windows anymore, together
+    /// with all handlers attached to it.
+    ///
+    fn remove_registration(&mut self, registration: HotkeyId) {
+        for id in self.attached_to(registration) {
+            self.attached.remove(&id);
+            self.handlers.remove(&id);
+            self.window.release_id(id);
+        }
+        self.handlers.remove(&registration);
+        self.window.release_id(registration);
+    }
+
+    /// Register the hotkey of an existing handler with windows again, using the same id.
+    ///
+    fn register_again(&self, id: HotkeyId) -> bool {
+        let Some(handler) = self.handlers.get(&id) else {
+            return false;
+        };
+
+        let reg_ok = unsafe {
+            RegisterHotKey(
+                self.window.hwnd.0,
+                id.0 as i32,
+                handler.modifiers,
+                handler.virtual_key.to_vk_code() as u32,
+            )
+        };
+        reg_ok != 0
+    }
+
+    /// Wait for a single hotkey event and return the id of the triggered hotkey, without executing
+    /// its callback. Like `handle_hotkey`, only hotkeys where all extra keys match are returned.
+    /// The function call will block until a hotkey is triggered or it is interrupted.
+    ///
+    /// If the event is interrupted, `None` is returned. Hotkeys can be registered without a
+    /// callback when only this function is used.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    pub fn wait_for_any(&self) -> Option<HotkeyId> {
+        loop {
+            if let (hk_id, HotkeyState::Pressed) = self.next_event(true).ok()?? {
+                return Some(hk_id);
+            }
+        }
+    }
+
+    /// Register a new hotkey with a callback that is executed both when the hotkey is pressed and
+    /// when its main key is released again. The state is passed to the callback.
+    ///
+    /// Windows only reports hotkey presses, so the release is detected by polling the state of the
+    /// main key after the hotkey was triggered. While a release is pending, `handle_hotkey` polls
+    /// the message queue instead of blocking on it.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+    ///
+    pub fn register_with_state(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: impl Fn(HotkeyState) -> T + Send + 'static,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let mut modifiers = ModifiersKey::combine(modifiers_key);
+        if self.no_repeat {
+            modifiers |= ModifiersKey::NoRepeat.to_mod_code();
+        }
+
+        let callback = Arc::new(callback);
+        let mut handler = HotkeyCallback::new(
+            virtual_key,
+            modifiers,
+            Some(Arc::new({
+                let callback = callback.clone();
+                move || callback(HotkeyState::Pressed)
+            })),
+            merge_extra_keys(modifiers_key, extra_keys),
+        );
+        handler.on_release = Some(Arc::new(move || callback(HotkeyState::Released)));
+
+        self.register_handler(handler)
+    }
+
+    /// Register a new hotkey that only executes the callback when the foreground window matches
+    /// the `predicate`, for example to only react to a hotkey while a specific game is focused.
+    /// The predicate is checked in `handle_hotkey` after the hotkey was triggered.
+    ///
+    /// `RegisterHotKey` is global, so the key combination is still consumed by the hotkey while
+    /// another window is in the foreground, it just doesn't execute the callback.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getforegroundwindow>
+    ///
+    pub fn register_when_foreground(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        predicate: impl Fn(&ForegroundWindow) -> bool + Send + 'static,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let mut modifiers = ModifiersKey::combine(modifiers_key);
+        if self.no_repeat {
+            modifiers |= ModifiersKey::NoRepeat.to_mod_code();
+        }
+
+        let mut handler = HotkeyCallback::new(
+            virtual_key,
+            modifiers,
+            Some(Arc::new(callback)),
+            merge_extra_keys(modifiers_key, None),
+        );
+        handler.foreground = Some(Arc::new(predicate));
+
+        self.register_handler(handler)
+    }
+
+    /// Switch the application mode that selects the callback of the hotkeys registered with
+    /// `register_modal`. The mode is read when a hotkey is triggered, so switching modes doesn't
+    /// touch any registrations. The initial mode is `0`.
+    ///
+    /// This only takes a shared reference, so the mode can be switched from another thread while
+    /// `event_loop` is running.
+    ///
+    pub fn set_mode(&self, mode: u32) {
+        self.mode.store(mode, Ordering::SeqCst);
+    }
+
+    /// Get the current application mode, see `set_mode`.
+    ///
+    pub fn mode(&self) -> u32 {
+        self.mode.load(Ordering::SeqCst)
+    }
+
+    /// Register a new hotkey with a different callback for each application mode. When the hotkey
+    /// is triggered, the callback for the current mode (see `set_mode`) is executed. In modes
+    /// without a callback, the hotkey is still consumed, but nothing is executed.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register_modal(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callbacks: impl IntoIterator<Item = (u32, Box<dyn Fn() -> T + Send + 'static>)>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let mut modifiers = ModifiersKey::combine(modifiers_key);
+        if self.no_repeat {
+            modifiers |= ModifiersKey::NoRepeat.to_mod_code();
+        }
+
+        let mut handler = HotkeyCallback::new(
+            virtual_key,
+            modifiers,
+            None,
+            merge_extra_keys(modifiers_key, extra_keys),
+        );
+        handler.modal = Some(
+            callbacks
+                .into_iter()
+                .map(|(mode, callback)| {
+                    (
+                        mode,
+                        Arc::<dyn Fn() -> T + Send>::from(callback) as Arc<dyn Fn() -> T>,
+                    )
+                })
+                .collect(),
+        );
+
+        self.register_handler(handler)
+    }
+
+    /// Set the shared context that is passed to the callbacks registered with
+    /// `register_with_context`. This avoids capturing the same application state in every callback.
+    /// The callbacks only get a shared reference, so mutable state needs interior mutability, like
+    /// a `Mutex` or `RefCell` inside the context.
+    ///
+    /// Note: Hotkeys that are already registered keep the context that was set when they were
+    /// registered. Setting a new context only applies to registrations performed afterwards.
+    pub fn set_context<C: Send + Sync + 'static>(&mut self, context: C) {
+        self.context = Some(Arc::new(context));
+    }
+
+    /// Same as `register_extrakeys`, but the callback gets a reference to the context of the
+    /// manager, see `set_context`.
+    ///
+    /// Returns `HotkeyError::MissingContext` if no context is set, or if it's not of type `C`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register_with_context<C: Send + Sync + 'static>(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: impl Fn(&C) -> T + Send + 'static,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let context = self
+            .context
+            .clone()
+            .and_then(|context| context.downcast::<C>().ok())
+            .ok_or(HotkeyError::MissingContext)?;
+
+        self.register_extrakeys(
+            virtual_key,
+            modifiers_key,
+            extra_keys,
+            Some(move || callback(&context)),
+        )
+    }
+
+    /// Wait for the next hotkey event, which is either a triggered hotkey where all extra keys
+    /// match, or the release of a hotkey that has a release callback. Return `None` if interrupted,
+    /// and an error if the message queue can't be read, for example because the hidden window was
+    /// destroyed.
+    ///
+    /// Without `blocking`, `None` is also returned right away if there is no pending event.
+    ///
+    fn next_event(&self, blocking: bool) -> Result<Option<(HotkeyId, HotkeyState)>, HotkeyError> {
+        loop {
+            if let Some(hk_id) = self.poll_release() {
+                return Ok(Some((hk_id, HotkeyState::Released)));
+            }
+
+            let mut msg = std::mem::MaybeUninit::<MSG>::uninit();
+
+            if blocking && self.pending_releases().is_empty() {
+                // Block and read a message from the message queue. Filtered to receive messages
+                // from WM_NULL to WM_HOTKEY
+                let ok = unsafe {
+                    GetMessageW(msg.as_mut_ptr(), self.window.hwnd.0, WM_NULL, WM_HOTKEY)
+                };
+                // -1 signals an error, in which case the message is not initialized
+                if ok == -1 {
+                    return Err(HotkeyError::MessageLoopFailed(unsafe { GetLastError() }));
+                }
+                if ok == 0 {
+                    continue;
+                }
+            } else {
+                // Don't block when polling or when a release needs to be detected
+                let ok = unsafe {
+                    PeekMessageW(
+                        msg.as_mut_ptr(),
+                        self.window.hwnd.0,
+                        WM_NULL,
+                        WM_HOTKEY,
+                        PM_REMOVE,
+                    )
+                };
+                if ok == 0 {
+                    if !blocking {
+                        return Ok(None);
+                    }
+                    sleep(self.release_poll_interval);
+                    continue;
+                }
+            }
+
+            let msg = unsafe { msg.assume_init() };
+
+            if WM_HOTKEY == msg.message {
+                let registration = HotkeyId(msg.wParam as u16);
+
+                let matching = self.dispatch_target(registration, get_raw_keystate);
+                if let Some((hk_id, handler)) = matching {
+                    if handler.taps.as_ref().is_none_or(|taps| taps.tap()) {
+                        let mut pending = self.pending_releases();
+                        if handler.on_release.is_some() && !pending.contains(&hk_id) {
+                            pending.push(hk_id);
+                        }
+                        return Ok(Some((hk_id, HotkeyState::Pressed)));
+                    }
+                }
+            } else if WM_NULL == msg.message {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Find the handler that is dispatched for a hotkey event of the given registration. The extra
+    /// keys are checked against `key_state`, in the format returned by `GetAsyncKeyState`. If
+    /// other handlers are attached to the registration, the most specific handler that matches is
+    /// used.
+    ///
+    fn dispatch_target(
+        &self,
+        registration: HotkeyId,
+        key_state: impl Fn(VirtualKey) -> u16,
+    ) -> Option<(HotkeyId, &HotkeyCallback<T>)> {
+        std::iter::once(registration)
+            .chain(self.attached_to(registration))
+            .filter_map(|id| Some((id, self.handlers.get(&id)?)))
+            .filter(|(_, handler)| {
+                handler.enabled
+                    && handler.extra_keys_match(&key_state)
+                    && handler.foreground_matches()
+            })
+            .max_by_key(|(id, handler)| {
+                let extra_keys = handler.extra_keys.as_ref().map_or(0, Vec::len);
+                (extra_keys, std::cmp::Reverse(id.0))
+            })
+    }
+
+    /// Lock the list of hotkeys whose release is pending.
+    ///
+    fn pending_releases(&self) -> MutexGuard<'_, Vec<HotkeyId>> {
+        self.pending_releases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Check the hotkeys with a pending release and return the first one whose main key is not
+    /// pressed anymore.
+    ///
+    fn poll_release(&self) -> Option<HotkeyId> {
+        let mut pending = self.pending_releases();
+        // Forget about hotkeys that were unregistered in the meantime
+        pending.retain(|hk_id| self.handlers.contains_key(hk_id));
+
+        let index = pending
+            .iter()
+            .position(|hk_id| !get_global_keystate(self.handlers[hk_id].virtual_key))?;
+        Some(pending.remove(index))
+    }
+
+    /// Replace the callback of an already registered hotkey. The windows registration is kept as
+    /// is, so there is no window in which the hotkey could be claimed by another application.
+    ///
+    /// Returns `HotkeyError::UnknownHotkeyId` if no hotkey is registered for the given id.
+    ///
+    pub fn set_callback(
+        &mut self,
+        id: HotkeyId,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<(), HotkeyError> {
+        let handler = self
+            .handlers
+            .get_mut(&id)
+            .ok_or(HotkeyError::UnknownHotkeyId(id))?;
+        handler.callback = Some(Arc::new(callback));
+        Ok(())
+    }
+
+    /// Same as `handle_hotkey`, but returns an error instead of `None` if the message queue can't be
+    /// read, for example because the hidden window was destroyed.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    pub fn try_handle_hotkey(&self) -> Result<Option<T>, HotkeyError> {
+        loop {
+            let Some((hk_id, state)) = self.next_event(true)? else {
+                return Ok(None);
+            };
+
+            if let Some(cb) = self.callback_for(hk_id, state) {
+                log_debug!("dispatching hotkey with id {} ({:?})", hk_id, state);
+                return Ok(Some(cb()));
+            }
+        }
+    }
+
+    /// Execute the callbacks of all pending hotkey events and return their results, without
+    /// blocking. This is an alternative to `handle_hotkey` and `event_loop` for applications that
+    /// already run a loop of their own, like a game or render loop, and can call `poll` once per
+    /// iteration. An empty `Vec` is returned if no hotkey was triggered since the last call.
+    ///
+    /// Releases of hotkeys registered with `register_with_state` are only detected while polling,
+    /// so their precision depends on how often `poll` is called.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-peekmessagew>
+    ///
+    pub fn poll(&self) -> Vec<T> {
+        let mut results = Vec::new();
+        // Reading the message queue without blocking can't fail, so errors don't need handling
+        while let Ok(Some((hk_id, state))) = self.next_event(false) {
+            if let Some(cb) = self.callback_for(hk_id, state) {
+                log_debug!("dispatching hotkey with id {} ({:?})", hk_id, state);
+                results.push(cb());
+            }
+        }
+        results
+    }
+
+    /// Get the callback of a hotkey that needs to be executed for the given state, if any.
+    ///
+    fn callback_for(&self, id: HotkeyId, state: HotkeyState) -> Option<&Arc<dyn Fn() -> T>> {
+        let handler = self.handlers.get(&id)?;
+        match (state, &handler.modal) {
+            (HotkeyState::Pressed, Some(modal)) => modal.get(&self.mode()),
+            (HotkeyState::Pressed, None) => handler.callback.as_ref(),
+            (HotkeyState::Released, _) => handler.on_release.as_ref(),
+        }
+    }
+
+    /// Same as `event_loop`, but returns an error if the message queue can't be read, instead of
+    /// stopping silently. Returns `Ok` if the loop was interrupted.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getmessagew>
+    ///
+    pub fn try_event_loop(&self) -> Result<(), HotkeyError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("event_loop").entered();
+
+        while let Some(result) = self.try_handle_hotkey()? {
+            if let Some(results) = &self.results {
+                let _ = results.send(result);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the hotkeys of this manager are still registered with windows, and return the
+    /// ids of the hotkeys that were lost. Lost hotkeys are removed from the manager.
+    ///
+    /// Windows doesn't allow querying the owner of a key combination, so each registration is
+    /// probed by unregistering it and registering it again right away. If the registration is
+    /// not ours anymore, unregistering it fails. If a different application registered the
+    /// combination in the short moment in between, registering it again fails with
+    /// `ERROR_HOTKEY_ALREADY_REGISTERED`. Hotkeys sharing a registration are lost together.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn verify_registrations(&mut self) -> Vec<HotkeyId> {
+        let mut lost = Vec::new();
+        for id in self.registered_with_windows() {
+            let ok = unsafe { UnregisterHotKey(self.window.hwnd.0, id.0 as i32) };
+            if ok != 0 && self.register_again(id) {
+                continue;
+            }
+
+            log_warn!("hotkey with id {} is no longer registered", id);
+            lost.push(id);
+            lost.extend(self.attached_to(id));
+            self.remove_registration(id);
+        }
+
+        lost
+    }
+
+    /// Get the ids of all hotkeys that are currently registered with this manager, in no particular
+    /// order.
+    ///
+    pub fn registered_ids(&self) -> Vec<HotkeyId> {
+        self.handlers.keys().copied().collect()
+    }
+
+    /// Get the number of hotkeys that are currently registered with this manager.
+    ///
+    pub fn registration_count(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Get the ids of all registered hotkeys whose keys are currently held down. A hotkey counts as
+    /// held down if its main key, all of its modifiers and all of its extra keys are pressed.
+    /// Additional pressed keys don't matter.
+    ///
+    /// This only observes the keystate, so it also reports hotkeys that are held down while they
+    /// are paused or while a different application has the focus.
+    ///
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+    ///
+    pub fn pressed_ids(&self) -> Vec<HotkeyId> {
+        self.handlers
+            .iter()
+            .filter(|(_, handler)| {
+                get_global_keystate(handler.virtual_key)
+                    && handler.modifiers_pressed()
+                    && handler.extra_keys_pressed()
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Enable or disable a registered hotkey. A disabled hotkey stays registered with windows, so
+    /// the key combination is still claimed by this application, but its events are dropped and
+    /// the callbacks aren't executed. This is cheaper than unregistering and registering the hotkey
+    /// again, and there is no window in which another application could claim the combination.
+    ///
+    /// Hotkeys are enabled when they are registered. Returns `HotkeyError::UnknownHotkeyId` if no
+    /// hotkey is registered for the given id.
+    ///
+    pub fn set_enabled(&mut self, id: HotkeyId, enabled: bool) -> Result<(), HotkeyError> {
+        let handler = self
+            .handlers
+            .get_mut(&id)
+            .ok_or(HotkeyError::UnknownHotkeyId(id))?;
+        handler.enabled = enabled;
+        Ok(())
     }
-}
 
-#[derive(Debug)]
-pub struct HotkeyManager<T> {
-    hwnd: DropHWND,
-    id: u16,
-    handlers: HashMap<HotkeyId, HotkeyCallback<T>>,
-    no_repeat: bool,
-    _unimpl_send_sync: PhantomData<*const u8>,
-}
+    /// Move a registered hotkey to a new key combination, keeping its callbacks and settings. The
+    /// new combination is registered first, and the old one is only unregistered once that
+    /// succeeded, so there is no moment in which neither of them is active. If the old combination
+    /// can't be unregistered, the new registration is rolled back and the hotkey stays as is.
+    ///
+    /// The `NoRepeat` modifier is kept as it was for the old registration. When rebinding to the
+    /// same main key and modifiers, for example to change the extra keys, the windows registration
+    /// is kept as is and only the extra keys are replaced, so the id stays the same.
+    ///
+    /// Returns the id of the new registration, the old id becomes invalid unless it is returned.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    pub fn rebind(
+        &mut self,
+        id: HotkeyId,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let old = self
+            .handlers
+            .get_mut(&id)
+            .ok_or(HotkeyError::UnknownHotkeyId(id))?;
 
-unsafe impl<T> Send for HotkeyManager<T> {}
-unsafe impl<T> Sync for HotkeyManager<T> {}
+        let no_repeat = old.modifiers & ModifiersKey::NoRepeat.to_mod_code();
+        let modifiers = ModifiersKey::combine(modifiers_key) | no_repeat;
+        if old.virtual_key.to_vk_code() == virtual_key.to_vk_code() && old.modifiers == modifiers {
+            old.virtual_key = virtual_key;
+            old.extra_keys = merge_extra_keys(modifiers_key, extra_keys);
+            return Ok(id);
+        }
 
-impl<T> Default for HotkeyManager<T> {
-    fn default() -> Self {
-        Self::new()
+        let mut handler = HotkeyCallback::new(
+            virtual_key,
+            modifiers,
+            old.callback.clone(),
+            merge_extra_keys(modifiers_key, extra_keys),
+        );
+        handler.on_release = old.on_release.clone();
+        handler.taps = old
+            .taps
+            .as_ref()
+            .map(|taps| TapCounter::new(taps.required, taps.within));
+        handler.foreground = old.foreground.clone();
+        handler.enabled = old.enabled;
+        handler.modal = old.modal.clone();
+        handler.ignore_lock_keys = old.ignore_lock_keys;
+
+        let new_id = self.register_handler(handler)?;
+        if let Err(err) = self.unregister(id) {
+            if let Err(rollback_err) = self.unregister(new_id) {
+                log_error!(
+                    "failed to roll back the registration with id {}: {}",
+                    new_id,
+                    rollback_err
+                );
+            }
+            return Err(err);
+        }
+        Ok(new_id)
     }
-}
 
-impl<T> HotkeyManager<T> {
-    /// Enable or disable the automatically applied `ModKey::NoRepeat` modifier. By default, this
-    /// option is set to `true` which causes all hotkey registration calls to add the `NoRepeat`
-    /// modifier, thereby disabling automatic retriggers of hotkeys when holding down the keys.
+    /// Check if the extra keys of a registered hotkey are currently all pressed. This can be used
+    /// to find out why a hotkey doesn't execute its callback, since hotkey events with unmet extra
+    /// keys are dropped silently.
     ///
-    /// When this option is disabled, the `ModKey::NoRepeat` can still be manually added while
-    /// registering hotkeys.
+    /// Returns `None` if no hotkey is registered for the given id, and `Some(true)` for hotkeys
+    /// without extra keys.
     ///
-    /// Note: Setting this flag doesn't change previously registered hotkeys. It only applies to
-    /// registrations performed after calling this function.
-    pub fn set_no_repeat(&mut self, no_repeat: bool) {
-        self.no_repeat = no_repeat;
+    /// ## Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getasynckeystate>
+    ///
+    pub fn extra_keys_satisfied(&self, id: HotkeyId) -> Option<bool> {
+        self.handlers
+            .get(&id)
+            .map(|handler| handler.extra_keys_pressed())
     }
-}
 
-impl<T> HotkeyManagerImpl<T> for HotkeyManager<T> {
-    fn new() -> HotkeyManager<T> {
-        let hwnd = create_hidden_window().unwrap_or(DropHWND(std::ptr::null_mut()));
-        HotkeyManager {
-            hwnd,
-            id: 0,
-            handlers: HashMap::new(),
-            no_repeat: true,
-            _unimpl_send_sync: PhantomData,
+    /// Register a new hotkey that only executes the callback once it has been pressed `taps` times
+    /// within the `within` duration, for example double-tapping a key.
+    ///
+    /// The time window starts with the first press. If it lapses before the tap count is reached,
+    /// the counter is reset and the next press starts a new window.
+    ///
+    /// The `ModKey::NoRepeat` modifier is always added for multi-tap hotkeys, regardless of the
+    /// `no_repeat` setting, so that holding down the keys isn't counted as multiple taps.
+    ///
+    /// Returns `HotkeyError::InvalidTapCount` if `taps` is less than 2, use `register` for hotkeys
+    /// that trigger on a single press.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register_multi_tap(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        taps: u8,
+        within: Duration,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HotkeyError> {
+        if taps < 2 {
+            return Err(HotkeyError::InvalidTapCount(taps));
         }
+        let modifiers = ModifiersKey::combine(modifiers_key) | ModifiersKey::NoRepeat.to_mod_code();
+
+        let mut handler = HotkeyCallback::new(
+            virtual_key,
+            modifiers,
+            Some(Arc::new(callback)),
+            merge_extra_keys(modifiers_key, None),
+        );
+        handler.taps = Some(TapCounter::new(taps, within));
+
+        self.register_handler(handler)
     }
 
-    fn register_extrakeys(
+    /// Same as `register_extrakeys`, but override the `no_repeat` setting of the manager for this
+    /// registration only. When `no_repeat` is `true`, the `ModKey::NoRepeat` modifier is added.
+    /// When it is `false`, the hotkey retriggers while being held down, unless `ModKey::NoRepeat`
+    /// is passed explicitly in `modifiers_key`.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register_with_no_repeat(
         &mut self,
         virtual_key: VirtualKey,
         modifiers_key: Option<&[ModifiersKey]>,
         extra_keys: Option<&[VirtualKey]>,
+        no_repeat: bool,
+        callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let mut modifiers = ModifiersKey::combine(modifiers_key);
+        if no_repeat {
+            modifiers |= ModifiersKey::NoRepeat.to_mod_code();
+        }
+
+        let callback = callback.map(|cb| Arc::new(cb) as Arc<dyn Fn() -> T + 'static>);
+        self.register_handler(HotkeyCallback::new(
+            virtual_key,
+            modifiers,
+            callback,
+            merge_extra_keys(modifiers_key, extra_keys),
+        ))
+    }
+
+    /// Register a new hotkey from the raw `fsModifiers` and virtual keycode values of
+    /// `RegisterHotKey`, for example when they come from a legacy configuration. The values are
+    /// passed to windows as is, so the `no_repeat` setting of the manager is not applied. Add
+    /// `MOD_NOREPEAT` to `modifiers` to disable the automatic retriggers.
+    ///
+    /// The keycode must still fit into a byte, and must not be a modifier key.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register_raw(
+        &mut self,
+        modifiers: u32,
+        vk_code: u16,
         callback: Option<impl Fn() -> T + Send + 'static>,
     ) -> Result<HotkeyId, HotkeyError> {
-        let register_id = HotkeyId(self.id);
-        self.id += 1;
+        let callback = callback.map(|cb| Arc::new(cb) as Arc<dyn Fn() -> T + 'static>);
+        self.register_handler(HotkeyCallback::new(
+            VirtualKey::CustomKeyCode(vk_code),
+            modifiers,
+            callback,
+            None,
+        ))
+    }
 
+    /// Same as `register_extrakeys`, but retry the registration if it fails for a reason that
+    /// might be temporary, for example while another application is still releasing its hotkeys
+    /// during startup. The registration is attempted up to `attempts` times, waiting `delay`
+    /// before the first retry and doubling the delay for every further retry. This blocks the
+    /// calling thread while waiting.
+    ///
+    /// A key combination that is already registered by another application fails right away,
+    /// since retrying wouldn't help.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register_with_retry(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        attempts: u32,
+        delay: Duration,
+        callback: impl Fn() -> T + Send + 'static,
+    ) -> Result<HotkeyId, HotkeyError> {
         let mut modifiers = ModifiersKey::combine(modifiers_key);
         if self.no_repeat {
             modifiers |= ModifiersKey::NoRepeat.to_mod_code();
         }
 
+        let callback = Arc::new(callback) as Arc<dyn Fn() -> T + 'static>;
+        let mut delay = delay;
+        let mut attempt = 1;
+        loop {
+            let handler = HotkeyCallback::new(
+                virtual_key,
+                modifiers,
+                Some(callback.clone()),
+                merge_extra_keys(modifiers_key, extra_keys),
+            );
+            match self.try_register_handler(handler) {
+                Err((_, os_error))
+                    if attempt < attempts
+                        && os_error != 0
+                        && os_error != ERROR_HOTKEY_ALREADY_REGISTERED =>
+                {
+                    log_debug!(
+                        "registering hotkey {} failed (os error {}), retrying in {:?}",
+                        virtual_key,
+                        os_error,
+                        delay
+                    );
+                    sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result.map_err(|(err, _)| err),
+            }
+        }
+    }
+
+    /// Find the id of the windows registration for the key combination of the handler, if the
+    /// combination is registered already. The `NoRepeat` flag is not part of the combination.
+    ///
+    fn registration_of(&self, handler: &HotkeyCallback<T>) -> Option<HotkeyId> {
+        let modifiers =
+            |handler: &HotkeyCallback<T>| handler.modifiers & !ModifiersKey::NoRepeat.to_mod_code();
+        self.handlers
+            .iter()
+            .filter(|(id, _)| !self.attached.contains_key(id))
+            .find(|(_, other)| {
+                other.virtual_key.to_vk_code() == handler.virtual_key.to_vk_code()
+                    && modifiers(other) == modifiers(handler)
+            })
+            .map(|(id, _)| *id)
+    }
+
+    /// Register the hotkey with windows and add the handler once the registration succeeded.
+    ///
+    fn register_handler(&mut self, handler: HotkeyCallback<T>) -> Result<HotkeyId, HotkeyError> {
+        self.try_register_handler(handler).map_err(|(err, _)| err)
+    }
+
+    /// Same as `register_handler`, but also return the OS error code if `RegisterHotKey` failed,
+    /// or `0` if the hotkey was rejected before calling it.
+    ///
+    fn try_register_handler(
+        &mut self,
+        mut handler: HotkeyCallback<T>,
+    ) -> Result<HotkeyId, (HotkeyError, u32)> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "register",
+            virtual_key = %handler.virtual_key,
+            modifiers = handler.modifiers
+        )
+        .entered();
+
+        // RegisterHotKey only uses the low byte, so a larger keycode would register another key
+        let vk_code = handler.virtual_key.to_vk_code();
+        if vk_code > 0xFF {
+            return Err((HotkeyError::InvalidKeyCode(vk_code), 0));
+        }
+        // A hotkey needs a non-modifier main key, the modifiers are passed separately
+        if handler.virtual_key.is_modifier() {
+            return Err((HotkeyError::ModifierAsMainKey(handler.virtual_key), 0));
+        }
+
+        handler.ignore_lock_keys |= self.ignore_lock_keys;
+
+        // Windows allows a key combination only once, so a handler for a combination that is
+        // already registered shares the existing registration, and is told apart by its extra keys.
+        // With the same extra keys it could never be dispatched, so it is rejected like windows
+        // rejects a duplicate registration.
+        let registration = self.registration_of(&handler);
+        if let Some(registration) = registration {
+            let extra_keys = extra_key_set(&handler);
+            let duplicate = std::iter::once(registration)
+                .chain(self.attached_to(registration))
+                .any(|id| extra_key_set(&self.handlers[&id]) == extra_keys);
+            if duplicate {
+                log_debug!("hotkey {} is already registered", handler.virtual_key);
+                return Err((
+                    HotkeyError::RegistrationFailed,
+                    ERROR_HOTKEY_ALREADY_REGISTERED,
+                ));
+            }
+        }
+
+        let register_id = self.window.next_id().map_err(|err| (err, 0))?;
+        if let Some(registration) = registration {
+            log_debug!(
+                "attached hotkey {} with id {} to id {}",
+                handler.virtual_key,
+                register_id,
+                registration
+            );
+            self.attached.insert(register_id, registration);
+            self.handlers.insert(register_id, handler);
+            return Ok(register_id);
+        }
+
         let reg_ok = unsafe {
             RegisterHotKey(
-                self.hwnd.0,
+                self.window.hwnd.0,
                 register_id.0 as i32,
-                modifiers,
-                virtual_key.to_vk_code() as u32,
+                handler.modifiers,
+                handler.virtual_key.to_vk_code() as u32,
             )
         };
+        // Read the error right away, before other calls overwrite it
+        let os_error = if reg_ok == 0 {
+            unsafe { GetLastError() }
+        } else {
+            0
+        };
+
+        if self.track_layout && handler.virtual_key.is_layout_dependent() {
+            let vk_code = handler.virtual_key.to_vk_code() as u32;
+            let scan_code = unsafe { MapVirtualKeyExW(vk_code, MAPVK_VK_TO_VSC, self.layout) };
+            handler.scan_code = (scan_code != 0).then_some(scan_code);
+        }
 
         if reg_ok == 0 {
-            Err(HotkeyError::RegistrationFailed)
+            log_debug!("failed to register hotkey {}", handler.virtual_key);
+            self.window.release_id(register_id);
+            Err((HotkeyError::RegistrationFailed, os_error))
         } else {
-            // Add the HotkeyCallback to the handlers when the hotkey was registered
-            let callback = callback.map(|cb| Box::new(cb) as Box<dyn Fn() -> T + 'static>);
-            self.handlers.insert(
-                register_id,
-                HotkeyCallback {
-                    callback,
-                    extra_keys: extra_keys.map(|keys| keys.to_vec()),
-                },
+            log_debug!(
+                "registered hotkey {} with id {}",
+                handler.virtual_key,
+                register_id
             );
-
+            self.handlers.insert(register_id, handler);
             Ok(register_id)
         }
     }
+}
+
+/// Get the keycodes of the extra keys of a handler, sorted and deduped, to compare them regardless
+/// of their order.
+fn extra_key_set<T>(handler: &HotkeyCallback<T>) -> Vec<u16> {
+    let mut keys: Vec<u16> = handler
+        .extra_keys
+        .iter()
+        .flatten()
+        .map(|vk| vk.to_vk_code())
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+/// Registers the hotkeys again if the closure passed to `with_hotkeys_paused` panics.
+struct ResumeOnUnwind<'a, T>(&'a mut HotkeyManager<T>);
+
+impl<T> Drop for ResumeOnUnwind<'_, T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            if let Err(err) = self.0.resume() {
+                log_error!("failed to register the hotkeys again: {}", err);
+            }
+        }
+    }
+}
+
+impl<T> HotkeyManagerImpl<T> for HotkeyManager<T> {
+    fn new() -> HotkeyManager<T> {
+        Self::with_window(Arc::new(HotkeyWindow::new()))
+    }
+
+    fn register_extrakeys(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        self.register_with_no_repeat(
+            virtual_key,
+            modifiers_key,
+            extra_keys,
+            self.no_repeat,
+            callback,
+        )
+    }
+
+    fn register_shared(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: Arc<dyn Fn() -> T + Send + Sync + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let mut modifiers = ModifiersKey::combine(modifiers_key);
+        if self.no_repeat {
+            modifiers |= ModifiersKey::NoRepeat.to_mod_code();
+        }
+
+        self.register_handler(HotkeyCallback::new(
+            virtual_key,
+            modifiers,
+            Some(callback),
+            merge_extra_keys(modifiers_key, extra_keys),
+        ))
+    }
 
     fn register(
         &mut self,
@@ -136,20 +1470,53 @@ impl<T> HotkeyManagerImpl<T> for HotkeyManager<T> {
         self.register_extrakeys(virtual_key, modifiers_key, None, callback)
     }
 
+    /// If other handlers are attached to the registration of the hotkey, the registration is moved
+    /// to the first of them. This briefly unregisters the key combination, and if it can't be
+    /// registered again, the attached handlers are removed as well and
+    /// `HotkeyError::RegistrationFailed` is returned.
+    ///
     fn unregister(&mut self, id: HotkeyId) -> Result<(), HotkeyError> {
-        let ok = unsafe { UnregisterHotKey(self.hwnd.0, id.0 as i32) };
+        // Attached handlers don't have a registration of their own
+        if self.attached.remove(&id).is_some() {
+            log_debug!("unregistered attached hotkey with id {}", id);
+            self.handlers.remove(&id);
+            self.window.release_id(id);
+            return Ok(());
+        }
+
+        let ok = unsafe { UnregisterHotKey(self.window.hwnd.0, id.0 as i32) };
 
         match ok {
-            0 => Err(HotkeyError::UnregistrationFailed),
+            0 => {
+                log_debug!("failed to unregister hotkey with id {}", id);
+                Err(HotkeyError::UnregistrationFailed)
+            }
             _ => {
+                log_debug!("unregistered hotkey with id {}", id);
                 self.handlers.remove(&id);
+                self.window.release_id(id);
+
+                let attached = self.attached_to(id);
+                let Some((&successor, others)) = attached.split_first() else {
+                    return Ok(());
+                };
+                self.attached.remove(&successor);
+                for other in others {
+                    self.attached.insert(*other, successor);
+                }
+                if !self.register_again(successor) {
+                    self.remove_registration(successor);
+                    return Err(HotkeyError::RegistrationFailed);
+                }
                 Ok(())
             }
         }
     }
 
     fn unregister_all(&mut self) -> Result<(), HotkeyError> {
-        let ids: Vec<_> = self.handlers.keys().copied().collect();
+        // Unregister the attached handlers first, so the registrations don't need to be moved
+        let mut ids: Vec<_> = self.handlers.keys().copied().collect();
+        ids.sort_by_key(|id| !self.attached.contains_key(id));
         for id in ids {
             self.unregister(id)?;
         }
@@ -157,50 +1524,26 @@ impl<T> HotkeyManagerImpl<T> for HotkeyManager<T> {
         Ok(())
     }
 
+    /// Errors reading the message queue are logged and reported as `None`, use
+    /// `try_handle_hotkey` to get the error.
+    ///
     fn handle_hotkey(&self) -> Option<T> {
-        loop {
-            let mut msg = std::mem::MaybeUninit::<MSG>::uninit();
-
-            // Block and read a message from the message queue. Filtered to receive messages from
-            // WM_NULL to WM_HOTKEY
-            let ok = unsafe { GetMessageW(msg.as_mut_ptr(), self.hwnd.0, WM_NULL, WM_HOTKEY) };
-
-            if ok != 0 {
-                let msg = unsafe { msg.assume_init() };
-
-                if WM_HOTKEY == msg.message {
-                    let hk_id = HotkeyId(msg.wParam as u16);
-
-                    // Get the callback for the received ID
-                    if let Some(handler) = self.handlers.get(&hk_id) {
-                        match &handler.extra_keys {
-                            Some(keys) => {
-                                if !keys.iter().any(|vk| !get_global_keystate(*vk)) {
-                                    if let Some(cb) = &handler.callback {
-                                        return Some(cb());
-                                    }
-                                }
-                            }
-                            None => {
-                                if let Some(cb) = &handler.callback {
-                                    return Some(cb());
-                                }
-                            }
-                        }
-                    }
-                } else if WM_NULL == msg.message {
-                    return None;
-                }
-            }
-        }
+        self.try_handle_hotkey().unwrap_or_else(|err| {
+            log_error!("failed to handle hotkey: {}", err);
+            None
+        })
     }
 
+    /// Same as `try_event_loop`, but the error is only logged.
+    ///
     fn event_loop(&self) {
-        while self.handle_hotkey().is_some() {}
+        if let Err(err) = self.try_event_loop() {
+            log_error!("hotkey event loop stopped: {}", err);
+        }
     }
 
     fn interrupt_handle(&self) -> InterruptHandle {
-        InterruptHandle(self.hwnd.0)
+        InterruptHandle(self.window.hwnd.0)
     }
 }
 
@@ -210,9 +1553,25 @@ impl<T> Drop for HotkeyManager<T> {
     }
 }
 
+/// Combine the extra keys with the ones implied by the modifiers, like `ModifiersKey::LWin`.
+///
+fn merge_extra_keys(
+    modifiers_key: Option<&[ModifiersKey]>,
+    extra_keys: Option<&[VirtualKey]>,
+) -> Option<Vec<VirtualKey>> {
+    let mut keys = ModifiersKey::extra_keys(modifiers_key);
+    keys.extend_from_slice(extra_keys.unwrap_or_default());
+
+    if keys.is_empty() {
+        None
+    } else {
+        Some(keys)
+    }
+}
+
 /// Try to create a hidden "message-only" window
 ///
-fn create_hidden_window() -> Result<DropHWND, ()> {
+fn create_hidden_window() -> Result<DropHWND, HotkeyError> {
     let hwnd = unsafe {
         // Get the current module handle
         let hinstance = GetModuleHandleA(std::ptr::null_mut());
@@ -237,8 +1596,321 @@ fn create_hidden_window() -> Result<DropHWND, ()> {
         )
     };
     if hwnd.is_null() {
-        Err(())
+        Err(HotkeyError::WindowCreationFailed(unsafe { GetLastError() }))
     } else {
         Ok(DropHWND(hwnd))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows_sys::Win32::UI::WindowsAndMessaging::PostMessageW;
+
+    const MODIFIERS: &[ModifiersKey] =
+        &[ModifiersKey::Ctrl, ModifiersKey::Alt, ModifiersKey::Shift];
+
+    #[test]
+    fn rebind_rolls_back_if_old_hotkey_cant_be_unregistered() {
+        let mut hkm = HotkeyManager::<()>::try_new().unwrap();
+        let id = hkm
+            .register(VirtualKey::F23, Some(MODIFIERS), Some(|| {}))
+            .unwrap();
+        // Unregister the hotkey behind the back of the manager, so unregistering it fails
+        assert_ne!(
+            unsafe { UnregisterHotKey(hkm.window.hwnd.0, id.0 as i32) },
+            0
+        );
+
+        let result = hkm.rebind(id, VirtualKey::F24, Some(MODIFIERS), None);
+        assert_eq!(result, Err(HotkeyError::UnregistrationFailed));
+        // The new registration was rolled back, and the hotkey still has its old id
+        assert_eq!(hkm.registered_ids(), vec![id]);
+    }
+
+    #[test]
+    fn hotkeys_are_resumed_if_paused_closure_panics() {
+        let mut hkm = HotkeyManager::<()>::try_new().unwrap();
+        hkm.register(VirtualKey::F23, Some(MODIFIERS), Some(|| {}))
+            .unwrap();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            hkm.with_hotkeys_paused(|| panic!("capturing the hotkey failed"))
+        }));
+        assert!(result.is_err());
+
+        // The combination is taken again, so another manager can't register it
+        let mut other = HotkeyManager::<()>::try_new().unwrap();
+        assert_eq!(
+            other.register(VirtualKey::F23, Some(MODIFIERS), Some(|| {})),
+            Err(HotkeyError::RegistrationFailed)
+        );
+    }
+
+    #[test]
+    fn shared_managers_use_the_same_window() {
+        let first = HotkeyManager::<()>::try_new_shared().unwrap();
+        let second = HotkeyManager::<()>::new_shared();
+        assert!(Arc::ptr_eq(&first.window, &second.window));
+        assert!(!first.window.hwnd.0.is_null());
+    }
+
+    #[test]
+    fn builder_sets_no_repeat_bit() {
+        let no_repeat = ModifiersKey::NoRepeat.to_mod_code();
+        for (enabled, expected) in [(true, no_repeat), (false, 0)] {
+            let mut hkm = HotkeyManager::<()>::builder().no_repeat(enabled).build();
+            let id = hkm
+                .register(VirtualKey::F23, Some(MODIFIERS), Some(|| {}))
+                .unwrap();
+            assert_eq!(hkm.handlers[&id].modifiers & no_repeat, expected);
+        }
+    }
+
+    #[test]
+    fn disabled_hotkeys_are_not_dispatched() {
+        let mut hkm = HotkeyManager::<u32>::try_new().unwrap();
+        let id = hkm
+            .register(VirtualKey::F23, Some(MODIFIERS), Some(|| 1))
+            .unwrap();
+
+        hkm.set_enabled(id, false).unwrap();
+        press(&hkm, id);
+        assert_eq!(hkm.poll(), Vec::<u32>::new());
+
+        hkm.set_enabled(id, true).unwrap();
+        press(&hkm, id);
+        assert_eq!(hkm.poll(), vec![1]);
+
+        assert_eq!(
+            hkm.set_enabled(HotkeyId(id.0 + 1), false),
+            Err(HotkeyError::UnknownHotkeyId(HotkeyId(id.0 + 1)))
+        );
+    }
+
+    #[test]
+    fn registration_count_follows_unregister() {
+        let mut hkm = HotkeyManager::<()>::try_new().unwrap();
+        let first = hkm
+            .register(VirtualKey::F23, Some(MODIFIERS), Some(|| {}))
+            .unwrap();
+        let second = hkm
+            .register(VirtualKey::F24, Some(MODIFIERS), Some(|| {}))
+            .unwrap();
+        assert_eq!(hkm.registration_count(), 2);
+
+        hkm.unregister(first).unwrap();
+        assert_eq!(hkm.registration_count(), 1);
+        assert_eq!(hkm.registered_ids(), vec![second]);
+    }
+
+    #[test]
+    fn nothing_pressed_without_input() {
+        let mut hkm = HotkeyManager::<()>::try_new().unwrap();
+        assert_eq!(hkm.pressed_ids(), vec![]);
+
+        hkm.register(VirtualKey::F23, Some(MODIFIERS), Some(|| {}))
+            .unwrap();
+        assert_eq!(hkm.pressed_ids(), vec![]);
+    }
+
+    #[test]
+    fn extra_key_variants_share_a_registration() {
+        let mut hkm = HotkeyManager::<u32>::try_new().unwrap();
+        let left = hkm
+            .register_extrakeys(
+                VirtualKey::F23,
+                Some(MODIFIERS),
+                Some(&[VirtualKey::LShift]),
+                Some(|| 1),
+            )
+            .unwrap();
+        let right = hkm
+            .register_extrakeys(
+                VirtualKey::F23,
+                Some(MODIFIERS),
+                Some(&[VirtualKey::RShift]),
+                Some(|| 2),
+            )
+            .unwrap();
+        assert_eq!(hkm.attached.get(&right), Some(&left));
+
+        // Only the handler whose extra keys are held is dispatched
+        let held = |key: VirtualKey| move |vk: VirtualKey| if vk == key { 0x8000 } else { 0 };
+        let dispatch = |key_state| {
+            let (_, handler) = hkm.dispatch_target(left, key_state)?;
+            Some((handler.callback.as_ref()?)())
+        };
+        assert_eq!(dispatch(held(VirtualKey::LShift)), Some(1));
+        assert_eq!(dispatch(held(VirtualKey::RShift)), Some(2));
+        assert_eq!(dispatch(held(VirtualKey::B)), None);
+
+        // The same extra keys again can never be dispatched, so they are rejected
+        assert_eq!(
+            hkm.register_extrakeys(
+                VirtualKey::F23,
+                Some(MODIFIERS),
+                Some(&[VirtualKey::RShift, VirtualKey::RShift]),
+                Some(|| 3),
+            ),
+            Err(HotkeyError::RegistrationFailed)
+        );
+        assert_eq!(hkm.registration_count(), 2);
+    }
+
+    /// Post a `WM_HOTKEY` message for the hotkey, as if its key combination was pressed.
+    fn press<T>(hkm: &HotkeyManager<T>, id: HotkeyId) {
+        let posted = unsafe { PostMessageW(hkm.window.hwnd.0, WM_HOTKEY, id.0 as usize, 0) };
+        assert_ne!(posted, 0);
+    }
+
+    #[test]
+    fn stateful_callback_sees_press_and_release() {
+        let mut hkm = HotkeyManager::<HotkeyState>::try_new().unwrap();
+        let id = hkm
+            .register_with_state(VirtualKey::F23, Some(MODIFIERS), None, |state| state)
+            .unwrap();
+
+        press(&hkm, id);
+        // The release is pending after the press, and detected right away since F23 is not
+        // actually held down
+        assert_eq!(hkm.next_event(false), Ok(Some((id, HotkeyState::Pressed))));
+        assert_eq!(*hkm.pending_releases(), vec![id]);
+        assert_eq!(hkm.poll_release(), Some(id));
+        assert!(hkm.pending_releases().is_empty());
+
+        press(&hkm, id);
+        assert_eq!(
+            hkm.poll(),
+            vec![HotkeyState::Pressed, HotkeyState::Released]
+        );
+    }
+
+    #[test]
+    fn event_loop_sends_results_to_channel() {
+        let mut hkm = HotkeyManager::<u32>::try_new().unwrap();
+        let id = hkm
+            .register(VirtualKey::F23, Some(MODIFIERS), Some(|| 7))
+            .unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        hkm.set_result_sender(Some(sender));
+
+        press(&hkm, id);
+        hkm.interrupt_handle().interrupt();
+        assert_eq!(hkm.try_event_loop(), Ok(()));
+        assert_eq!(receiver.try_recv(), Ok(7));
+
+        // handle_hotkey returns the value instead of sending it
+        press(&hkm, id);
+        assert_eq!(hkm.handle_hotkey(), Some(7));
+        assert!(receiver.try_recv().is_err());
+
+        // Without a receiver, the results are dropped and the event loop keeps running
+        drop(receiver);
+        press(&hkm, id);
+        press(&hkm, id);
+        hkm.interrupt_handle().interrupt();
+        assert_eq!(hkm.try_event_loop(), Ok(()));
+    }
+
+    #[test]
+    fn poll_returns_pending_results_without_blocking() {
+        let mut hkm = HotkeyManager::<u32>::try_new().unwrap();
+        let id = hkm
+            .register(VirtualKey::F23, Some(MODIFIERS), Some(|| 5))
+            .unwrap();
+
+        press(&hkm, id);
+        assert_eq!(hkm.poll(), vec![5]);
+        // Nothing is pending anymore, so this returns right away
+        assert_eq!(hkm.poll(), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn try_new_creates_a_window() {
+        assert!(!HotkeyManager::<()>::try_new()
+            .unwrap()
+            .window
+            .hwnd
+            .0
+            .is_null());
+    }
+
+    #[test]
+    fn modifier_is_rejected_as_main_key() {
+        let mut hkm = HotkeyManager::<()>::try_new().unwrap();
+        for key in [VirtualKey::Shift, VirtualKey::LControl] {
+            assert_eq!(
+                hkm.register(key, Some(MODIFIERS), Some(|| {})),
+                Err(HotkeyError::ModifierAsMainKey(key))
+            );
+        }
+        assert_eq!(hkm.registration_count(), 0);
+    }
+
+    #[test]
+    fn shared_callback_runs_for_both_hotkeys() {
+        let mut hkm = HotkeyManager::<usize>::try_new().unwrap();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let callback: Arc<dyn Fn() -> usize + Send + Sync> = Arc::new({
+            let calls = calls.clone();
+            move || calls.fetch_add(1, Ordering::SeqCst) + 1
+        });
+        let first = hkm
+            .register_shared(VirtualKey::F23, Some(MODIFIERS), None, callback.clone())
+            .unwrap();
+        let second = hkm
+            .register_shared(VirtualKey::F24, Some(MODIFIERS), None, callback.clone())
+            .unwrap();
+
+        press(&hkm, first);
+        press(&hkm, second);
+        assert_eq!(hkm.poll(), vec![1, 2]);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn layout_change_reregisters_for_the_same_physical_key() {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+            LoadKeyboardLayoutW, KLF_NOTELLSHELL,
+        };
+
+        let mut hkm = HotkeyManager::<()>::try_new().unwrap();
+        hkm.set_track_layout(true);
+        let id = hkm
+            .register(VirtualKey::Semicolon, Some(MODIFIERS), Some(|| {}))
+            .unwrap();
+        let scan_code = hkm.handlers[&id].scan_code.unwrap();
+
+        // The German layout has a different OEM key at the position of the US semicolon
+        let name: Vec<u16> = "00000407\0".encode_utf16().collect();
+        let german = unsafe { LoadKeyboardLayoutW(name.as_ptr(), KLF_NOTELLSHELL) };
+        assert!(!german.is_null());
+        let vk_code = unsafe { MapVirtualKeyExW(scan_code, MAPVK_VSC_TO_VK, german) } as u16;
+
+        hkm.sync_to_layout(german).unwrap();
+        let key = VirtualKey::CustomKeyCode(vk_code);
+        assert_eq!(hkm.handlers[&id].virtual_key, key);
+
+        // The hotkey is registered with windows for the new keycode, under the same id
+        assert_eq!(hkm.registered_ids(), vec![id]);
+        let mut other = HotkeyManager::<()>::try_new().unwrap();
+        assert_eq!(
+            other.register(key, Some(MODIFIERS), Some(|| {})),
+            Err(HotkeyError::RegistrationFailed)
+        );
+    }
+
+    #[test]
+    fn id_allocator_stays_in_application_range() {
+        let mut ids = IdAllocator::default();
+        for expected in 0..=MAX_HOTKEY_ID {
+            assert_eq!(ids.next(), Some(expected));
+        }
+        // Only released ids are handed out after the application range is used up
+        assert_eq!(ids.next(), None);
+        ids.release(5);
+        assert_eq!(ids.next(), Some(5));
+        assert_eq!(ids.next(), None);
+    }
+}
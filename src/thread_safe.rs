@@ -3,8 +3,11 @@ use std::marker::PhantomData;
 use std::sync::mpsc::channel;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::thread::spawn;
 use std::thread::JoinHandle;
+use std::thread::ThreadId;
+use std::time::Duration;
 
 use crate::error::HotkeyError;
 use crate::keys::ModifiersKey;
@@ -12,13 +15,22 @@ use crate::keys::VirtualKey;
 use crate::single_thread;
 use crate::HotkeyId;
 use crate::HotkeyManagerImpl;
+use crate::HotkeyState;
 use crate::InterruptHandle;
 
 pub struct Hotkey<T: 'static> {
     virtual_key: VirtualKey,
     modifiers_key: Option<Vec<ModifiersKey>>,
     extra_keys: Option<Vec<VirtualKey>>,
-    callback: Option<Box<dyn Fn() -> T + Send + 'static>>,
+    callback: Option<HotkeyCallbackFn<T>>,
+}
+
+/// Callback of a `Hotkey` that is sent to the backend thread. Shared callbacks are passed through
+/// as is, so they don't need to be boxed again.
+enum HotkeyCallbackFn<T: 'static> {
+    Boxed(Box<dyn Fn() -> T + Send + 'static>),
+    Shared(Arc<dyn Fn() -> T + Send + Sync + 'static>),
+    Stateful(Box<dyn Fn(HotkeyState) -> T + Send + 'static>),
 }
 
 impl<T> fmt::Debug for Hotkey<T>
@@ -47,6 +59,10 @@ enum HotkeyMessage<T: 'static> {
     HandleHotkey(Sender<Option<T>>),
     Unregister(Sender<Result<(), HotkeyError>>, HotkeyId),
     UnregisterAll(Sender<Result<(), HotkeyError>>),
+    Pause(Sender<Result<(), HotkeyError>>),
+    Resume(Sender<Result<(), HotkeyError>>),
+    SetReleasePollInterval(Sender<()>, Duration),
+    SetResultSender(Sender<()>, Option<Sender<T>>),
     EventLoop(Sender<()>),
     InterruptHandle(Sender<InterruptHandle>),
     Exit(Sender<()>),
@@ -57,15 +73,95 @@ pub struct HotkeyManager<T: 'static> {
     no_repeat: bool,
     _phantom: PhantomData<T>,
     sender: Sender<HotkeyMessage<T>>,
+    interrupt: Arc<InterruptHandle>,
     backend_handle: Option<JoinHandle<()>>,
 }
 
+/// Cloneable handle for registering hotkeys with a `HotkeyManager` from other threads, created by
+/// `HotkeyManager::handle`. The handle only holds a channel to the backend thread, so it can be
+/// cloned and moved around cheaply. The backend thread is still owned by the `HotkeyManager`, and
+/// once the manager is dropped all calls return `HotkeyError::BackendGone`.
+///
+/// The backend thread processes one request at a time. While it is running the event loop,
+/// registrations through the handle wait until the event loop is interrupted, for example with
+/// `HotkeyHandle::interrupt`.
+///
+#[derive(Debug)]
+pub struct HotkeyHandle<T: 'static> {
+    no_repeat: bool,
+    sender: Sender<HotkeyMessage<T>>,
+    interrupt: Arc<InterruptHandle>,
+}
+
 struct TSHotkeyManagerBackend<T: 'static> {
     hkm: single_thread::HotkeyManager<T>,
     receiver: Receiver<HotkeyMessage<T>>,
 }
 
+/// Builder for a `HotkeyManager`, created by `HotkeyManager::builder`.
+///
+#[derive(Debug)]
+pub struct HotkeyManagerBuilder<T: 'static> {
+    no_repeat: bool,
+    release_poll_interval: Option<Duration>,
+    results: Option<Sender<T>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: 'static + Send> HotkeyManagerBuilder<T> {
+    /// Set whether the `ModKey::NoRepeat` modifier is automatically applied to all registrations.
+    /// Defaults to `true`, see `HotkeyManager::set_no_repeat`.
+    ///
+    pub fn no_repeat(mut self, no_repeat: bool) -> Self {
+        self.no_repeat = no_repeat;
+        self
+    }
+
+    /// Set the interval in which the key state is polled while waiting for a hotkey to be
+    /// released. Defaults to 10ms, see `HotkeyManager::set_release_poll_interval`.
+    ///
+    pub fn release_poll_interval(mut self, interval: Duration) -> Self {
+        self.release_poll_interval = Some(interval);
+        self
+    }
+
+    /// Set a channel that receives the return values of the callbacks executed by the event loop,
+    /// see `HotkeyManager::set_result_sender`.
+    ///
+    pub fn result_sender(mut self, sender: Sender<T>) -> Self {
+        self.results = Some(sender);
+        self
+    }
+
+    /// Create the `HotkeyManager` with the configured settings. This launches the background
+    /// thread, same as `HotkeyManager::new`.
+    ///
+    pub fn build(self) -> HotkeyManager<T> {
+        let mut hkm = HotkeyManager::new();
+        hkm.set_no_repeat(self.no_repeat);
+        if let Some(interval) = self.release_poll_interval {
+            // The backend was just started, so it can't be gone yet
+            let _ = hkm.set_release_poll_interval(interval);
+        }
+        if self.results.is_some() {
+            let _ = hkm.set_result_sender(self.results);
+        }
+        hkm
+    }
+}
+
 impl<T: 'static> HotkeyManager<T> {
+    /// Create a builder to configure a new `HotkeyManager` explicitly.
+    ///
+    pub fn builder() -> HotkeyManagerBuilder<T> {
+        HotkeyManagerBuilder {
+            no_repeat: true,
+            release_poll_interval: None,
+            results: None,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Enable or disable the automatically applied `ModKey::NoRepeat` modifier. By default, this
     /// option is set to `true` which causes all hotkey registration calls to add the `NoRepeat`
     /// modifier, thereby disabling automatic retriggers of hotkeys when holding down the keys.
@@ -78,6 +174,173 @@ impl<T: 'static> HotkeyManager<T> {
     pub fn set_no_repeat(&mut self, no_repeat: bool) {
         self.no_repeat = no_repeat;
     }
+
+    /// Create a cloneable handle for registering hotkeys from other threads. The handle uses the
+    /// `no_repeat` setting of the manager at the time it is created.
+    ///
+    pub fn handle(&self) -> HotkeyHandle<T> {
+        HotkeyHandle {
+            no_repeat: self.no_repeat,
+            sender: self.sender.clone(),
+            interrupt: self.interrupt.clone(),
+        }
+    }
+}
+
+impl<T: 'static> HotkeyManager<T> {
+    /// Get the id of the backend thread, which executes the callbacks.
+    ///
+    pub(crate) fn backend_thread(&self) -> Option<ThreadId> {
+        self.backend_handle
+            .as_ref()
+            .map(|backend| backend.thread().id())
+    }
+}
+
+impl<T> Clone for HotkeyHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            no_repeat: self.no_repeat,
+            sender: self.sender.clone(),
+            interrupt: self.interrupt.clone(),
+        }
+    }
+}
+
+impl<T: 'static + Send> HotkeyHandle<T> {
+    /// Register a new hotkey with the manager of this handle. See
+    /// `HotkeyManagerImpl::register_extrakeys` for the arguments.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-registerhotkey>
+    ///
+    pub fn register(
+        &self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let callback_boxed =
+            callback.map(|cb| HotkeyCallbackFn::Boxed(Box::new(cb) as Box<dyn Fn() -> T + Send>));
+
+        send_register(
+            &self.sender,
+            virtual_key,
+            modifiers_key,
+            extra_keys,
+            self.no_repeat,
+            callback_boxed,
+        )
+    }
+
+    /// Unregister a hotkey of the manager of this handle, regardless of which handle registered
+    /// it.
+    ///
+    /// # Windows API Functions used
+    /// - <https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-unregisterhotkey>
+    ///
+    pub fn unregister(&self, id: HotkeyId) -> Result<(), HotkeyError> {
+        send_message(&self.sender, |channel| {
+            HotkeyMessage::Unregister(channel, id)
+        })?
+    }
+
+    /// Interrupt the event loop of the manager of this handle, same as `InterruptHandle::interrupt`.
+    ///
+    pub fn interrupt(&self) {
+        self.interrupt.interrupt();
+    }
+}
+
+impl<T: 'static + Send> HotkeyManager<T> {
+    /// Temporarily unregister all hotkeys with windows while running `f`, and register them again
+    /// with the same ids afterwards. The callbacks are kept as is. This can be used to capture a new
+    /// key combination without triggering the existing hotkeys, for example while the user is
+    /// assigning a new hotkey in a settings UI.
+    ///
+    /// The result of `f` is wrapped in a `Result`, since pausing and resuming the hotkeys can fail.
+    /// If a hotkey can't be registered again after `f` returns, for example because another
+    /// application claimed the combination in the meantime, it is removed from the manager and
+    /// `HotkeyError::RegistrationFailed` is returned. If `f` panics, the hotkeys are registered
+    /// again before the panic continues.
+    ///
+    pub fn with_hotkeys_paused<R>(&mut self, f: impl FnOnce() -> R) -> Result<R, HotkeyError> {
+        send_message(&self.sender, HotkeyMessage::Pause)??;
+        let guard = ResumeOnUnwind(&self.sender);
+        let result = f();
+        send_message(guard.0, HotkeyMessage::Resume)??;
+
+        Ok(result)
+    }
+
+    /// Set the interval in which the key state is polled while waiting for a hotkey with a release
+    /// callback to be released. By default, the interval is 10ms. See the single threaded
+    /// `set_release_poll_interval`.
+    ///
+    pub fn set_release_poll_interval(&mut self, interval: Duration) -> Result<(), HotkeyError> {
+        send_message(&self.sender, |channel| {
+            HotkeyMessage::SetReleasePollInterval(channel, interval)
+        })
+    }
+
+    /// Set a channel that receives the return values of the callbacks executed by `event_loop`.
+    /// See the single threaded `set_result_sender`.
+    ///
+    pub fn set_result_sender(&mut self, sender: Option<Sender<T>>) -> Result<(), HotkeyError> {
+        send_message(&self.sender, |channel| {
+            HotkeyMessage::SetResultSender(channel, sender)
+        })
+    }
+
+    /// Same as `register_extrakeys`, but override the `no_repeat` setting of the manager for this
+    /// registration only. When `no_repeat` is `true`, the `ModKey::NoRepeat` modifier is added.
+    /// When it is `false`, the hotkey retriggers while being held down, unless `ModKey::NoRepeat`
+    /// is passed explicitly in `modifiers_key`.
+    ///
+    pub fn register_with_no_repeat(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        no_repeat: bool,
+        callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        let callback_boxed =
+            callback.map(|cb| HotkeyCallbackFn::Boxed(Box::new(cb) as Box<dyn Fn() -> T + Send>));
+
+        send_register(
+            &self.sender,
+            virtual_key,
+            modifiers_key,
+            extra_keys,
+            no_repeat,
+            callback_boxed,
+        )
+    }
+
+    /// Register a new hotkey with a callback that is executed both when the hotkey is pressed and
+    /// when its main key is released again. The state is passed to the callback.
+    ///
+    /// Windows only reports hotkey presses, so the release is detected by polling the state of the
+    /// main key after the hotkey was triggered.
+    ///
+    pub fn register_with_state(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: impl Fn(HotkeyState) -> T + Send + 'static,
+    ) -> Result<HotkeyId, HotkeyError> {
+        send_register(
+            &self.sender,
+            virtual_key,
+            modifiers_key,
+            extra_keys,
+            self.no_repeat,
+            Some(HotkeyCallbackFn::Stateful(Box::new(callback))),
+        )
+    }
 }
 
 impl<T> TSHotkeyManagerBackend<T> {
@@ -90,40 +353,79 @@ impl<T> TSHotkeyManagerBackend<T> {
         Self { hkm, receiver }
     }
 
+    /// Process the messages until the manager exits. The responses are sent without checking the
+    /// result, since a caller that stopped waiting for a response is no reason to stop the backend.
+    ///
     fn backend_loop(&mut self) {
         while let Ok(msg) = self.receiver.recv() {
             match msg {
                 HotkeyMessage::Register(channel, hotkey) => {
-                    let return_value = self.hkm.register_extrakeys(
-                        hotkey.virtual_key,
-                        hotkey.modifiers_key.as_deref(),
-                        hotkey.extra_keys.as_deref(),
-                        hotkey.callback,
-                    );
-                    channel.send(return_value).unwrap();
+                    let return_value = match hotkey.callback {
+                        Some(HotkeyCallbackFn::Shared(callback)) => self.hkm.register_shared(
+                            hotkey.virtual_key,
+                            hotkey.modifiers_key.as_deref(),
+                            hotkey.extra_keys.as_deref(),
+                            callback,
+                        ),
+                        Some(HotkeyCallbackFn::Stateful(callback)) => self.hkm.register_with_state(
+                            hotkey.virtual_key,
+                            hotkey.modifiers_key.as_deref(),
+                            hotkey.extra_keys.as_deref(),
+                            callback,
+                        ),
+                        Some(HotkeyCallbackFn::Boxed(callback)) => self.hkm.register_extrakeys(
+                            hotkey.virtual_key,
+                            hotkey.modifiers_key.as_deref(),
+                            hotkey.extra_keys.as_deref(),
+                            Some(callback),
+                        ),
+                        None => self.hkm.register_extrakeys(
+                            hotkey.virtual_key,
+                            hotkey.modifiers_key.as_deref(),
+                            hotkey.extra_keys.as_deref(),
+                            None::<fn() -> T>,
+                        ),
+                    };
+                    let _ = channel.send(return_value);
                 }
                 HotkeyMessage::HandleHotkey(channel) => {
                     let return_value = self.hkm.handle_hotkey();
-                    channel.send(return_value).unwrap();
+                    let _ = channel.send(return_value);
                 }
                 HotkeyMessage::Unregister(channel, hotkey_id) => {
                     let return_value = self.hkm.unregister(hotkey_id);
-                    channel.send(return_value).unwrap();
+                    let _ = channel.send(return_value);
                 }
                 HotkeyMessage::UnregisterAll(channel) => {
                     let return_value = self.hkm.unregister_all();
-                    channel.send(return_value).unwrap();
+                    let _ = channel.send(return_value);
+                }
+                HotkeyMessage::Pause(channel) => {
+                    let return_value = self.hkm.pause();
+                    let _ = channel.send(return_value);
+                }
+                HotkeyMessage::Resume(channel) => {
+                    let return_value = self.hkm.resume();
+                    let _ = channel.send(return_value);
+                }
+                HotkeyMessage::SetReleasePollInterval(channel, interval) => {
+                    self.hkm.set_release_poll_interval(interval);
+                    let _ = channel.send(());
+                }
+                HotkeyMessage::SetResultSender(channel, sender) => {
+                    self.hkm.set_result_sender(sender);
+                    let _ = channel.send(());
                 }
                 HotkeyMessage::EventLoop(channel) => {
                     self.hkm.event_loop();
-                    channel.send(()).unwrap();
+                    let _ = channel.send(());
                 }
                 HotkeyMessage::InterruptHandle(channel) => {
                     let return_value = self.hkm.interrupt_handle();
-                    channel.send(return_value).unwrap();
+                    let _ = channel.send(return_value);
                 }
                 HotkeyMessage::Exit(channel) => {
-                    channel.send(()).unwrap();
+                    let _ = channel.send(());
                     return;
                 }
             }
@@ -134,14 +436,21 @@ impl<T> TSHotkeyManagerBackend<T> {
 impl<T: 'static + Send> HotkeyManagerImpl<T> for HotkeyManager<T> {
     fn new() -> Self {
         let (sender, receiver) = channel();
+        let (interrupt_sender, interrupt_receiver) = channel();
         let backend_handle = spawn(move || {
             let mut backend = TSHotkeyManagerBackend::<T>::new(receiver);
+            let _ = interrupt_sender.send(backend.hkm.interrupt_handle());
             backend.backend_loop();
         });
+        let interrupt = interrupt_receiver
+            .recv()
+            .unwrap_or(InterruptHandle(std::ptr::null_mut()));
+
         Self {
             no_repeat: true,
             _phantom: PhantomData,
             sender,
+            interrupt: Arc::new(interrupt),
             backend_handle: Some(backend_handle),
         }
     }
@@ -153,28 +462,30 @@ impl<T: 'static + Send> HotkeyManagerImpl<T> for HotkeyManager<T> {
         extra_keys: Option<&[VirtualKey]>,
         callback: Option<impl Fn() -> T + Send + 'static>,
     ) -> Result<HotkeyId, HotkeyError> {
-        let return_channel = channel();
-
-        let mut modifiers_key = modifiers_key.map(|keys| keys.to_vec());
-
-        if self.no_repeat {
-            modifiers_key
-                .get_or_insert_with(Vec::new)
-                .push(ModifiersKey::NoRepeat);
-        }
-
-        let callback_boxed = callback.map(|cb| Box::new(cb) as Box<dyn Fn() -> T + Send>);
+        self.register_with_no_repeat(
+            virtual_key,
+            modifiers_key,
+            extra_keys,
+            self.no_repeat,
+            callback,
+        )
+    }
 
-        let hotkey = Hotkey {
+    fn register_shared(
+        &mut self,
+        virtual_key: VirtualKey,
+        modifiers_key: Option<&[ModifiersKey]>,
+        extra_keys: Option<&[VirtualKey]>,
+        callback: Arc<dyn Fn() -> T + Send + Sync + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        send_register(
+            &self.sender,
             virtual_key,
             modifiers_key,
-            extra_keys: extra_keys.map(|keys| keys.to_vec()),
-            callback: callback_boxed,
-        };
-        self.sender
-            .send(HotkeyMessage::Register(return_channel.0, hotkey))
-            .unwrap();
-        return_channel.1.recv().unwrap()
+            extra_keys,
+            self.no_repeat,
+            Some(HotkeyCallbackFn::Shared(callback)),
+        )
     }
 
     fn register(
@@ -187,53 +498,129 @@ impl<T: 'static + Send> HotkeyManagerImpl<T> for HotkeyManager<T> {
     }
 
     fn unregister(&mut self, id: HotkeyId) -> Result<(), HotkeyError> {
-        let return_channel = channel();
-        self.sender
-            .send(HotkeyMessage::Unregister(return_channel.0, id))
-            .unwrap();
-        return_channel.1.recv().unwrap()
+        send_message(&self.sender, |channel| {
+            HotkeyMessage::Unregister(channel, id)
+        })?
     }
 
     fn unregister_all(&mut self) -> Result<(), HotkeyError> {
-        let return_channel = channel();
-        self.sender
-            .send(HotkeyMessage::UnregisterAll(return_channel.0))
-            .unwrap();
-        return_channel.1.recv().unwrap()
+        send_message(&self.sender, HotkeyMessage::UnregisterAll)?
     }
 
+    /// Same as the single threaded `handle_hotkey`, but `None` is also returned if the backend
+    /// thread is not running anymore.
+    ///
     fn handle_hotkey(&self) -> Option<T> {
-        let return_channel = channel();
-        self.sender
-            .send(HotkeyMessage::HandleHotkey(return_channel.0))
-            .unwrap();
-        return_channel.1.recv().unwrap()
+        send_message(&self.sender, HotkeyMessage::HandleHotkey).ok()?
     }
 
+    /// Same as the single threaded `event_loop`, but also returns if the backend thread is not
+    /// running anymore.
+    ///
     fn event_loop(&self) {
-        let return_channel = channel();
-        self.sender
-            .send(HotkeyMessage::EventLoop(return_channel.0))
-            .unwrap();
-        return_channel.1.recv().unwrap()
+        let _ = send_message(&self.sender, HotkeyMessage::EventLoop);
     }
 
+    /// Same as the single threaded `interrupt_handle`. If the backend thread is not running
+    /// anymore, the returned handle doesn't do anything.
+    ///
     fn interrupt_handle(&self) -> InterruptHandle {
-        let return_channel = channel();
-        self.sender
-            .send(HotkeyMessage::InterruptHandle(return_channel.0))
-            .unwrap();
-        return_channel.1.recv().unwrap()
+        send_message(&self.sender, HotkeyMessage::InterruptHandle)
+            .unwrap_or(InterruptHandle(std::ptr::null_mut()))
     }
 }
 
 impl<T> Drop for HotkeyManager<T> {
     fn drop(&mut self) {
-        let return_channel = channel();
-        self.sender
-            .send(HotkeyMessage::Exit(return_channel.0))
-            .unwrap();
-        return_channel.1.recv().unwrap();
-        self.backend_handle.take().unwrap().join().unwrap();
+        // The backend thread might already be gone, in which case there is nothing to shut down
+        let _ = send_message(&self.sender, HotkeyMessage::Exit);
+        if let Some(backend_handle) = self.backend_handle.take() {
+            let _ = backend_handle.join();
+        }
+    }
+}
+
+/// Registers the hotkeys again if the closure passed to `with_hotkeys_paused` panics.
+struct ResumeOnUnwind<'a, T: 'static>(&'a Sender<HotkeyMessage<T>>);
+
+impl<T> Drop for ResumeOnUnwind<'_, T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            if let Ok(Err(err)) = send_message(self.0, HotkeyMessage::Resume) {
+                log_error!("failed to register the hotkeys again: {}", err);
+            }
+        }
+    }
+}
+
+/// Send a message to the backend thread and wait for the response. Return
+/// `HotkeyError::BackendGone` if the backend thread is not running anymore, for example because a
+/// callback panicked.
+///
+fn send_message<T, R>(
+    backend: &Sender<HotkeyMessage<T>>,
+    message: impl FnOnce(Sender<R>) -> HotkeyMessage<T>,
+) -> Result<R, HotkeyError> {
+    let (sender, receiver) = channel();
+    backend
+        .send(message(sender))
+        .map_err(|_| HotkeyError::BackendGone)?;
+    receiver.recv().map_err(|_| HotkeyError::BackendGone)
+}
+
+/// Send a hotkey registration to the backend thread and wait for the result.
+///
+fn send_register<T>(
+    backend: &Sender<HotkeyMessage<T>>,
+    virtual_key: VirtualKey,
+    modifiers_key: Option<&[ModifiersKey]>,
+    extra_keys: Option<&[VirtualKey]>,
+    no_repeat: bool,
+    callback: Option<HotkeyCallbackFn<T>>,
+) -> Result<HotkeyId, HotkeyError> {
+    let mut modifiers_key = modifiers_key.map(|keys| keys.to_vec());
+
+    if no_repeat {
+        modifiers_key
+            .get_or_insert_with(Vec::new)
+            .push(ModifiersKey::NoRepeat);
+    }
+
+    let hotkey = Hotkey {
+        virtual_key,
+        modifiers_key,
+        extra_keys: extra_keys.map(|keys| keys.to_vec()),
+        callback,
+    };
+    send_message(backend, |channel| HotkeyMessage::Register(channel, hotkey))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_fail_after_backend_exited() {
+        let mut hkm = HotkeyManager::<()>::new();
+        let handle = hkm.handle();
+
+        // Shut down the backend behind the back of the manager
+        send_message(&hkm.sender, HotkeyMessage::Exit).unwrap();
+        hkm.backend_handle.take().unwrap().join().unwrap();
+
+        assert_eq!(
+            hkm.register(VirtualKey::F24, None, Some(|| {})),
+            Err(HotkeyError::BackendGone)
+        );
+        assert_eq!(
+            hkm.unregister(HotkeyId::new(0)),
+            Err(HotkeyError::BackendGone)
+        );
+        assert_eq!(
+            handle.register(VirtualKey::F24, None, None, Some(|| {})),
+            Err(HotkeyError::BackendGone)
+        );
+        // Dropping the manager doesn't panic either
+        drop(hkm);
     }
 }
@@ -0,0 +1,122 @@
+//! Stand-in `HotkeyManager` for platforms other than windows.
+//!
+//! System-wide hotkeys are only implemented with the windows API. To allow cross-platform crates
+//! to depend on this crate without guarding every use with `#[cfg(windows)]`, the manager still
+//! exists on other platforms, but every registration fails with
+//! `HotkeyError::UnsupportedPlatform`.
+//!
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::error::HotkeyError;
+use crate::keys::ModifiersKey;
+use crate::keys::VirtualKey;
+use crate::HotkeyId;
+use crate::HotkeyManagerImpl;
+use crate::InterruptHandle;
+
+#[derive(Debug)]
+pub struct HotkeyManager<T> {
+    _phantom: PhantomData<T>,
+}
+
+impl<T> HotkeyManager<T> {
+    /// Always returns `HotkeyError::UnsupportedPlatform`, use `new` to create a manager that
+    /// fails on registration instead.
+    ///
+    pub fn try_new() -> Result<Self, HotkeyError> {
+        Err(HotkeyError::UnsupportedPlatform)
+    }
+}
+
+impl<T> Default for HotkeyManager<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> HotkeyManagerImpl<T> for HotkeyManager<T> {
+    fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+
+    fn register_extrakeys(
+        &mut self,
+        _virtual_key: VirtualKey,
+        _modifiers_key: Option<&[ModifiersKey]>,
+        _extra_keys: Option<&[VirtualKey]>,
+        _callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        Err(HotkeyError::UnsupportedPlatform)
+    }
+
+    fn register_shared(
+        &mut self,
+        _virtual_key: VirtualKey,
+        _modifiers_key: Option<&[ModifiersKey]>,
+        _extra_keys: Option<&[VirtualKey]>,
+        _callback: Arc<dyn Fn() -> T + Send + Sync + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        Err(HotkeyError::UnsupportedPlatform)
+    }
+
+    fn register(
+        &mut self,
+        _virtual_key: VirtualKey,
+        _modifiers_key: Option<&[ModifiersKey]>,
+        _callback: Option<impl Fn() -> T + Send + 'static>,
+    ) -> Result<HotkeyId, HotkeyError> {
+        Err(HotkeyError::UnsupportedPlatform)
+    }
+
+    fn unregister(&mut self, _id: HotkeyId) -> Result<(), HotkeyError> {
+        Err(HotkeyError::UnsupportedPlatform)
+    }
+
+    /// Succeeds, since there are never any hotkeys to unregister.
+    ///
+    fn unregister_all(&mut self) -> Result<(), HotkeyError> {
+        Ok(())
+    }
+
+    /// Returns `None` right away, since no hotkey can ever be triggered.
+    ///
+    fn handle_hotkey(&self) -> Option<T> {
+        None
+    }
+
+    /// Returns right away, since no hotkey can ever be triggered.
+    ///
+    fn event_loop(&self) {}
+
+    fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotkeys_are_unsupported() {
+        assert!(matches!(
+            HotkeyManager::<()>::try_new(),
+            Err(HotkeyError::UnsupportedPlatform)
+        ));
+
+        let mut hkm = HotkeyManager::<()>::new();
+        assert_eq!(
+            hkm.register(VirtualKey::A, Some(&[ModifiersKey::Ctrl]), Some(|| {})),
+            Err(HotkeyError::UnsupportedPlatform)
+        );
+        assert_eq!(
+            hkm.unregister(HotkeyId::new(0)),
+            Err(HotkeyError::UnsupportedPlatform)
+        );
+        assert_eq!(hkm.unregister_all(), Ok(()));
+    }
+}